@@ -0,0 +1,150 @@
+use crate::{
+    components::{
+        item::{AreaOfEffect, AreaShape},
+        position::Position,
+    },
+    map::{game_map::GameMap, TileType},
+    viewshed::generate_viewshed,
+};
+
+/// Half the opening angle of cone shaped areas
+const CONE_HALF_ANGLE_DEGREES: f32 = 30.0;
+
+/// Gets all tiles hit by an area of effect item that is used from `origin` and aimed at
+/// `target`. Walls shield the tiles behind them.
+pub fn affected_tiles(
+    origin: &Position,
+    target: &Position,
+    aoe: &AreaOfEffect,
+    shape: AreaShape,
+    game_map: &GameMap,
+) -> Vec<Position> {
+    match shape {
+        AreaShape::Circle => tiles_in_radius(target, aoe.radius, game_map),
+        AreaShape::Cone => cone(origin, target, aoe.radius, game_map),
+        AreaShape::Line => line(origin, target, aoe.radius, game_map),
+    }
+}
+
+/// Tiles within the radius around the center that can be seen from the center
+fn tiles_in_radius(center: &Position, radius: u32, game_map: &GameMap) -> Vec<Position> {
+    let fov = generate_viewshed(center, game_map, radius as usize, true);
+
+    let mut tiles = vec![];
+    for x in 0..fov.width {
+        for y in 0..fov.height {
+            let pos = Position::new(x as i32, y as i32);
+            if fov.is_in_fov(x, y) && center.get_airline_distance(&pos) <= radius as i32 {
+                tiles.push(pos);
+            }
+        }
+    }
+    tiles
+}
+
+fn cone(origin: &Position, target: &Position, radius: u32, game_map: &GameMap) -> Vec<Position> {
+    if origin == target {
+        return vec![];
+    }
+
+    let aim = ((target.x - origin.x) as f32, (target.y - origin.y) as f32);
+    let aim_length = (aim.0.powi(2) + aim.1.powi(2)).sqrt();
+    let min_cos = CONE_HALF_ANGLE_DEGREES.to_radians().cos();
+
+    tiles_in_radius(origin, radius, game_map)
+        .into_iter()
+        .filter(|pos| {
+            if pos == origin {
+                return false;
+            }
+            let offset = ((pos.x - origin.x) as f32, (pos.y - origin.y) as f32);
+            let offset_length = (offset.0.powi(2) + offset.1.powi(2)).sqrt();
+            let cos = (aim.0 * offset.0 + aim.1 * offset.1) / (aim_length * offset_length);
+
+            cos >= min_cos
+        })
+        .collect()
+}
+
+fn line(origin: &Position, target: &Position, radius: u32, game_map: &GameMap) -> Vec<Position> {
+    if origin == target {
+        return vec![];
+    }
+
+    // One step per tile along the longer axis
+    let steps = (target.x - origin.x).abs().max((target.y - origin.y).abs()) as f32;
+    let step_x = (target.x - origin.x) as f32 / steps;
+    let step_y = (target.y - origin.y) as f32 / steps;
+
+    let mut tiles = vec![];
+    for i in 1..=radius {
+        let pos = Position::new(
+            origin.x + (step_x * i as f32).round() as i32,
+            origin.y + (step_y * i as f32).round() as i32,
+        );
+        let out_of_map =
+            pos.x < 0 || pos.x >= game_map.width || pos.y < 0 || pos.y >= game_map.height;
+        if out_of_map || game_map.tiles.get(&pos) == Some(&TileType::Wall) {
+            break;
+        }
+        tiles.push(pos);
+    }
+    tiles
+}
+
+#[test]
+fn can_shape_areas() {
+    use std::collections::{HashMap, HashSet};
+
+    let mut map = GameMap::new(
+        11,
+        11,
+        1,
+        HashMap::new(),
+        HashSet::new(),
+        HashSet::new(),
+        HashMap::new(),
+    );
+    let origin = Position::new(1, 5);
+    let target = Position::new(5, 5);
+
+    let circle = affected_tiles(
+        &origin,
+        &target,
+        &AreaOfEffect { radius: 2 },
+        AreaShape::Circle,
+        &map,
+    );
+    assert!(circle.contains(&Position::new(7, 5)));
+    assert!(!circle.contains(&Position::new(8, 5)));
+    assert!(!circle.contains(&origin));
+
+    let cone = affected_tiles(
+        &origin,
+        &target,
+        &AreaOfEffect { radius: 3 },
+        AreaShape::Cone,
+        &map,
+    );
+    assert!(cone.contains(&Position::new(4, 5)));
+    assert!(cone.contains(&Position::new(3, 6)));
+    assert!(!cone.contains(&Position::new(1, 6)));
+    assert!(!cone.contains(&Position::new(5, 5)));
+
+    map.tiles.insert(Position::new(5, 5), TileType::Wall);
+    let line = affected_tiles(
+        &origin,
+        &Position::new(3, 5),
+        &AreaOfEffect { radius: 8 },
+        AreaShape::Line,
+        &map,
+    );
+    assert_eq!(
+        line,
+        vec![
+            Position::new(2, 5),
+            Position::new(3, 5),
+            Position::new(4, 5)
+        ]
+    );
+}
@@ -0,0 +1,145 @@
+use std::collections::HashSet;
+
+use bevy::{prelude::*, window::RequestRedraw};
+
+use crate::{
+    components::{item::Item, position::Position, user_input::UserInput},
+    map::game_map::GameMap,
+    monster::Monster,
+    player::Player,
+    user_interface::ActionLog,
+    viewshed::{spot_new_monsters, Viewshed, PLAYER_VIEWSHED_LABEL},
+    GameState,
+};
+
+pub struct AutoExplorePlugin {}
+
+impl Plugin for AutoExplorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            auto_explore
+                .after(PLAYER_VIEWSHED_LABEL)
+                .before("await_input"),
+        );
+    }
+}
+
+/// Component that flags the player walking towards unexplored tiles on their own, one turn per
+/// frame.
+#[derive(Component, Default)]
+pub struct AutoExploring {
+    pub steps: u32,
+    /// Monsters in sight on the previous step, `None` before the first step
+    pub monsters_in_sight: Option<HashSet<Entity>>,
+}
+
+/// Takes a step towards the nearest unexplored tile for an auto-exploring player. Stops as soon
+/// as a monster comes into view, an item is found, the map is fully explored or any key or mouse
+/// button is pressed.
+#[allow(clippy::too_many_arguments)]
+fn auto_explore(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut app_state: ResMut<State<GameState>>,
+    mut player_query: Query<(Entity, &Position, &Viewshed, &mut AutoExploring), With<Player>>,
+    monster_query: Query<(Entity, &Position), With<Monster>>,
+    item_query: Query<&Position, With<Item>>,
+    map: Res<GameMap>,
+    mut user_input_res: ResMut<UserInput>,
+    mut action_log: ResMut<ActionLog>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    if *app_state.current() != GameState::AwaitingActionInput {
+        return;
+    }
+    let (player, player_pos, viewshed, mut auto_exploring) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    // Monsters that were in sight already when exploring started don't stop it
+    let monsters_in_sight = monster_query
+        .iter()
+        .filter(|(_, monster_pos)| viewshed.visible_tiles.contains(monster_pos))
+        .map(|(monster, _)| monster)
+        .collect();
+    let spotted_monster =
+        spot_new_monsters(&mut auto_exploring.monsters_in_sight, monsters_in_sight);
+
+    let mut stop_reason = None;
+    if keyboard_input.get_just_pressed().len() > 0 || mouse_input.get_just_pressed().len() > 0 {
+        // The input itself is handled by the other input systems
+        stop_reason = Some("You stop exploring.");
+    } else if spotted_monster {
+        stop_reason = Some("You spot a monster and stop exploring.");
+    } else if auto_exploring.steps > 0 && item_query.iter().any(|item_pos| item_pos == player_pos) {
+        stop_reason = Some("You find something and stop exploring.");
+    }
+
+    let next_step = match stop_reason {
+        Some(_) => None,
+        None => next_explore_step(&map, player_pos),
+    };
+
+    match next_step {
+        Some(step) => {
+            auto_exploring.steps += 1;
+            user_input_res.x = step.x - player_pos.x;
+            user_input_res.y = step.y - player_pos.y;
+
+            app_state
+                .set(GameState::PlayerTurn)
+                .expect("failed to set game state in auto_explore");
+            redraw.send(RequestRedraw);
+        }
+        None => {
+            let reason = stop_reason.unwrap_or("There is nothing left to explore here.");
+            action_log.entries.push(reason.to_owned());
+            commands.entity(player).remove::<AutoExploring>();
+        }
+    }
+}
+
+/// Finds the first step on the shortest path to the nearest reachable tile not yet visited
+fn next_explore_step(map: &GameMap, start: &Position) -> Option<Position> {
+    let path = pathfinding::directed::bfs::bfs(
+        start,
+        |pos| {
+            map.get_traversable_neighbours_with_distance(pos)
+                .into_iter()
+                .map(|(neighbour, _)| neighbour)
+        },
+        |pos| !map.visited_tiles.contains(pos),
+    )?;
+
+    path.get(1).cloned()
+}
+
+#[test]
+fn explores_towards_nearest_unvisited_tile() {
+    use std::collections::{HashMap, HashSet};
+
+    // A 5x1 corridor with the player in the middle and only the left end unexplored
+    let visited_tiles = HashSet::from([
+        Position::new(1, 0),
+        Position::new(2, 0),
+        Position::new(3, 0),
+        Position::new(4, 0),
+    ]);
+    let mut map = GameMap::new(
+        1,
+        5,
+        1,
+        HashMap::new(),
+        visited_tiles,
+        HashSet::new(),
+        HashMap::new(),
+    );
+    let start = Position::new(2, 0);
+
+    assert_eq!(next_explore_step(&map, &start), Some(Position::new(1, 0)));
+
+    map.visited_tiles.insert(Position::new(0, 0));
+    assert_eq!(next_explore_step(&map, &start), None);
+}
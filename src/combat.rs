@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng};
+
+use crate::{
+    components::{
+        combat_stats::CombatStats,
+        damage::{DamageTracker, SufferDamage},
+    },
+    user_interface::ActionLog,
+};
+
+/// Chance in percent to hit a defender without any defense
+const BASE_HIT_CHANCE: i32 = 90;
+/// Every point of defense makes the defender this much harder to hit, in percent
+const HIT_CHANCE_PER_DEFENSE: i32 = 3;
+/// No matter how good the defense, attacks hit at least this often, in percent
+const MIN_HIT_CHANCE: i32 = 50;
+/// Chance in percent that a hit is critical and deals double power
+const CRIT_CHANCE: i32 = 5;
+/// A hit always deals at least this much damage, even against heavy armor
+const MIN_DAMAGE: i32 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AttackOutcome {
+    Miss,
+    Hit(i32),
+    Critical(i32),
+}
+
+/// Chance in percent for an attack against the defender to land
+pub fn hit_chance(defender: &CombatStats) -> i32 {
+    (BASE_HIT_CHANCE - defender.defense * HIT_CHANCE_PER_DEFENSE).max(MIN_HIT_CHANCE)
+}
+
+/// Damage of a landed attack: the attacker's power (doubled on a critical hit) reduced by the
+/// defender's defense
+pub fn melee_damage(attacker: &CombatStats, defender: &CombatStats, critical: bool) -> i32 {
+    let power = if critical {
+        attacker.power * 2
+    } else {
+        attacker.power
+    };
+
+    (power - defender.defense).max(MIN_DAMAGE)
+}
+
+pub fn roll_attack(
+    attacker: &CombatStats,
+    defender: &CombatStats,
+    rng: &mut StdRng,
+) -> AttackOutcome {
+    let roll = rng.gen_range(0..100);
+
+    if roll >= hit_chance(defender) {
+        AttackOutcome::Miss
+    } else if roll < CRIT_CHANCE {
+        AttackOutcome::Critical(melee_damage(attacker, defender, true))
+    } else {
+        AttackOutcome::Hit(melee_damage(attacker, defender, false))
+    }
+}
+
+/// Resolves a melee attack, records the damage for the victim and logs the outcome.
+/// Returns the outcome of the attack.
+#[allow(clippy::too_many_arguments)]
+pub fn melee_attack(
+    damage_tracker: &mut ResMut<DamageTracker>,
+    action_log: &mut ActionLog,
+    rng: &mut StdRng,
+    attacker: Entity,
+    attacker_name: &str,
+    attacker_stats: &CombatStats,
+    victim: Entity,
+    victim_name: &str,
+    victim_stats: &CombatStats,
+) -> AttackOutcome {
+    let outcome = roll_attack(attacker_stats, victim_stats, rng);
+
+    match outcome {
+        AttackOutcome::Miss => action_log
+            .entries
+            .push(format!("{} misses {}", attacker_name, victim_name)),
+        AttackOutcome::Hit(damage) => SufferDamage::add_damage(
+            damage_tracker,
+            victim,
+            damage,
+            action_log,
+            Some(attacker),
+            attacker_name,
+            victim_name,
+        ),
+        AttackOutcome::Critical(damage) => {
+            SufferDamage::record_damage(
+                damage_tracker,
+                victim,
+                damage,
+                Some(attacker),
+                attacker_name,
+            );
+            action_log.entries.push(format!(
+                "{} crits {} for {}",
+                attacker_name, victim_name, damage
+            ));
+        }
+    }
+
+    outcome
+}
+
+#[test]
+fn defense_reduces_damage_down_to_minimum() {
+    let attacker = CombatStats {
+        max_hp: 10,
+        hp: 10,
+        defense: 0,
+        power: 5,
+    };
+    let mut defender = CombatStats {
+        max_hp: 10,
+        hp: 10,
+        defense: 2,
+        power: 0,
+    };
+
+    assert_eq!(melee_damage(&attacker, &defender, false), 3);
+    assert_eq!(melee_damage(&attacker, &defender, true), 8);
+
+    defender.defense = 20;
+    assert_eq!(melee_damage(&attacker, &defender, false), MIN_DAMAGE);
+    assert_eq!(hit_chance(&defender), MIN_HIT_CHANCE);
+}
+
+#[test]
+fn attacks_can_miss_hit_and_crit() {
+    use rand::SeedableRng;
+
+    let stats = CombatStats {
+        max_hp: 10,
+        hp: 10,
+        defense: 0,
+        power: 4,
+    };
+    let mut rng = StdRng::seed_from_u64(7);
+
+    let outcomes: Vec<AttackOutcome> = (0..1000)
+        .map(|_| roll_attack(&stats, &stats, &mut rng))
+        .collect();
+
+    assert!(outcomes.contains(&AttackOutcome::Miss));
+    assert!(outcomes.contains(&AttackOutcome::Hit(4)));
+    assert!(outcomes.contains(&AttackOutcome::Critical(8)));
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
@@ -0,0 +1,9 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Remains of a dead monster. Doesn't block movement.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Corpse {
+    /// Name of the monster that died here
+    pub name: String,
+}
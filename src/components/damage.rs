@@ -1,6 +1,7 @@
 use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct InflictsDamage {
     pub damage: i32,
 }
@@ -17,31 +18,52 @@ pub struct DamageTracker(pub HashMap<Entity, SufferDamage>);
 #[derive(Debug)]
 pub struct SufferDamage {
     pub amount: Vec<i32>,
+    /// Name of whatever dealt the most recent damage, e.g. to tell the player what killed them
+    pub last_attacker: String,
+    /// Who gets the credit for the most recent damage. `None` if nobody is to blame.
+    pub last_attacker_entity: Option<Entity>,
 }
 
 impl SufferDamage {
+    /// Records damage for the victim and logs the hit
     pub fn add_damage(
         tracker: &mut ResMut<DamageTracker>,
         victim: Entity,
         amount: i32,
         action_log: &mut ActionLog,
-        attacker_is_player: bool,
+        attacker: Option<Entity>,
+        attacker_name: &str,
+        victim_name: &str,
     ) {
-        if let Some(damage_entry) = (*tracker).0.get_mut(&victim) {
+        SufferDamage::record_damage(tracker, victim, amount, attacker, attacker_name);
+
+        action_log.entries.push(format!(
+            "{} hits {} for {}",
+            attacker_name, victim_name, amount
+        ));
+    }
+
+    /// Records damage for the victim without logging anything
+    pub fn record_damage(
+        tracker: &mut ResMut<DamageTracker>,
+        victim: Entity,
+        amount: i32,
+        attacker: Option<Entity>,
+        attacker_name: &str,
+    ) {
+        if let Some(damage_entry) = tracker.0.get_mut(&victim) {
             damage_entry.amount.push(amount);
+            damage_entry.last_attacker = attacker_name.to_owned();
+            damage_entry.last_attacker_entity = attacker;
         } else {
             tracker.0.insert(
                 victim,
                 SufferDamage {
                     amount: vec![amount],
+                    last_attacker: attacker_name.to_owned(),
+                    last_attacker_entity: attacker,
                 },
             );
         }
-
-        let text = match attacker_is_player {
-            true => format!("Player hits Monster for {}", amount),
-            false => format!("Monster hits Player for {}", amount),
-        };
-        action_log.entries.push(text);
     }
 }
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{Component, Entity};
+use serde::{Deserialize, Serialize};
+
+use super::combat_stats::CombatStats;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum EquipmentSlot {
+    Weapon,
+    Armor,
+    Shield,
+    Ring,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [EquipmentSlot; 4] = [
+        EquipmentSlot::Weapon,
+        EquipmentSlot::Armor,
+        EquipmentSlot::Shield,
+        EquipmentSlot::Ring,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EquipmentSlot::Weapon => "Weapon",
+            EquipmentSlot::Armor => "Armor",
+            EquipmentSlot::Shield => "Shield",
+            EquipmentSlot::Ring => "Ring",
+        }
+    }
+}
+
+/// Component for items that can be worn in an equipment slot. While worn, the bonuses are added
+/// to the wearer's `CombatStats`.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+    pub power_bonus: i32,
+    pub defense_bonus: i32,
+}
+
+impl Equippable {
+    pub fn apply_bonus(&self, stats: &mut CombatStats) {
+        stats.power += self.power_bonus;
+        stats.defense += self.defense_bonus;
+    }
+
+    pub fn remove_bonus(&self, stats: &mut CombatStats) {
+        stats.power -= self.power_bonus;
+        stats.defense -= self.defense_bonus;
+    }
+}
+
+/// Component holding the items the player is wearing, at most one per slot. Worn items are not
+/// part of the `Inventory` and don't take up any of its slots.
+#[derive(Component, Debug, Default)]
+pub struct Equipment {
+    pub slots: HashMap<EquipmentSlot, Entity>,
+}
+
+impl Equipment {
+    pub fn get(&self, slot: EquipmentSlot) -> Option<Entity> {
+        self.slots.get(&slot).copied()
+    }
+
+    pub fn is_worn(&self, item: Entity) -> bool {
+        self.slots.values().any(|worn| *worn == item)
+    }
+
+    /// Wears the item in the slot. Returns the item that was worn there before.
+    pub fn equip(&mut self, slot: EquipmentSlot, item: Entity) -> Option<Entity> {
+        self.slots.insert(slot, item)
+    }
+
+    /// Takes the item off. Returns false if it wasn't worn.
+    pub fn unequip(&mut self, item: Entity) -> bool {
+        let worn_count = self.slots.len();
+        self.slots.retain(|_, worn| *worn != item);
+        self.slots.len() < worn_count
+    }
+}
+
+#[test]
+fn equipment_bonus_is_removed_on_unequip() {
+    let mut stats = CombatStats {
+        max_hp: 100,
+        hp: 100,
+        defense: 0,
+        power: 5,
+    };
+    let sword = Equippable {
+        slot: EquipmentSlot::Weapon,
+        power_bonus: 3,
+        defense_bonus: 1,
+    };
+
+    sword.apply_bonus(&mut stats);
+    assert_eq!((stats.power, stats.defense), (8, 1));
+
+    sword.remove_bonus(&mut stats);
+    assert_eq!((stats.power, stats.defense), (5, 0));
+}
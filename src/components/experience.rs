@@ -0,0 +1,59 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Experience collected by the player and the level it amounts to
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+}
+
+/// Component for monsters, granting xp to whoever kills them
+#[derive(Component, Debug, Clone)]
+pub struct ExperienceReward {
+    pub xp: i32,
+}
+
+impl Experience {
+    pub fn new() -> Experience {
+        Experience { level: 1, xp: 0 }
+    }
+
+    /// Total xp needed for the next level, `None` once the highest level is reached.
+    /// `xp_thresholds[0]` is the xp needed for level 2.
+    pub fn next_level_xp(&self, xp_thresholds: &[i32]) -> Option<i32> {
+        xp_thresholds.get(self.level as usize - 1).copied()
+    }
+
+    /// Raises the level while there is enough xp for the next one. Returns the number of levels
+    /// gained.
+    pub fn level_up(&mut self, xp_thresholds: &[i32]) -> i32 {
+        let mut levels_gained = 0;
+        while let Some(next_level_xp) = self.next_level_xp(xp_thresholds) {
+            if self.xp < next_level_xp {
+                break;
+            }
+            self.level += 1;
+            levels_gained += 1;
+        }
+
+        levels_gained
+    }
+}
+
+#[test]
+fn can_level_up() {
+    let thresholds = [20, 50];
+    let mut experience = Experience::new();
+
+    experience.xp = 19;
+    assert_eq!(experience.level_up(&thresholds), 0);
+    assert_eq!(experience.next_level_xp(&thresholds), Some(20));
+
+    // Enough xp for two levels at once
+    experience.xp = 60;
+    assert_eq!(experience.level_up(&thresholds), 2);
+    assert_eq!(experience.level, 3);
+    assert_eq!(experience.next_level_xp(&thresholds), None);
+    assert_eq!(experience.level_up(&thresholds), 0);
+}
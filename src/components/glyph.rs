@@ -0,0 +1,8 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// Character drawn on top of an entity's sprite
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Glyph {
+    pub glyph: char,
+}
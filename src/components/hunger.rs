@@ -0,0 +1,91 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+/// How many turns each hunger state lasts before it gets worse
+pub const WELL_FED_DURATION: i32 = 150;
+pub const NORMAL_DURATION: i32 = 300;
+pub const HUNGRY_DURATION: i32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HungerState {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+impl HungerState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            HungerState::WellFed => "Well Fed",
+            HungerState::Normal => "Normal",
+            HungerState::Hungry => "Hungry",
+            HungerState::Starving => "Starving",
+        }
+    }
+}
+
+/// Component counting down the turns until its owner gets hungrier
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct HungerClock {
+    pub state: HungerState,
+    /// Turns left in the current state
+    pub duration: i32,
+}
+
+impl HungerClock {
+    pub fn well_fed() -> HungerClock {
+        HungerClock {
+            state: HungerState::WellFed,
+            duration: WELL_FED_DURATION,
+        }
+    }
+
+    /// Advances the clock by one turn. Returns the new state if it changed.
+    pub fn tick(&mut self) -> Option<HungerState> {
+        if self.state == HungerState::Starving {
+            return None;
+        }
+
+        self.duration -= 1;
+        if self.duration > 0 {
+            return None;
+        }
+
+        (self.state, self.duration) = match self.state {
+            HungerState::WellFed => (HungerState::Normal, NORMAL_DURATION),
+            HungerState::Normal => (HungerState::Hungry, HUNGRY_DURATION),
+            _ => (HungerState::Starving, 0),
+        };
+
+        Some(self.state)
+    }
+}
+
+/// Flag component for items that can be eaten to become well fed again
+#[derive(Component, Debug, Clone)]
+pub struct ProvidesFood {}
+
+#[test]
+fn hunger_gets_worse_over_time() {
+    let mut clock = HungerClock::well_fed();
+
+    for _ in 1..WELL_FED_DURATION {
+        assert_eq!(clock.tick(), None);
+    }
+    assert_eq!(clock.tick(), Some(HungerState::Normal));
+
+    for _ in 1..NORMAL_DURATION {
+        clock.tick();
+    }
+    assert_eq!(clock.tick(), Some(HungerState::Hungry));
+
+    for _ in 1..HUNGRY_DURATION {
+        clock.tick();
+    }
+    assert_eq!(clock.tick(), Some(HungerState::Starving));
+
+    // Starving is as bad as it gets
+    assert_eq!(clock.tick(), None);
+    assert_eq!(clock.state, HungerState::Starving);
+}
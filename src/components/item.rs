@@ -1,31 +1,59 @@
 use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
 
 /// Flag component to indicate an item
 #[derive(Component, Clone)]
 pub struct Item {}
 
-#[derive(Component)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Heals {
     pub heal_amount: i32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Ranged {
     pub range: u32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct AreaOfEffect {
     pub radius: u32,
 }
 
-pub const UNKNOWN_ITEM_NAME: &str = "<NOT IMPLEMENTED>";
+/// Shape of the area hit by an `AreaOfEffect` item. Items without a shape blast in a circle.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AreaShape {
+    /// Everything around the target tile within the radius
+    #[default]
+    Circle,
+    /// A wedge fanning out from the user towards the target, reaching as far as the radius
+    Cone,
+    /// A beam from the user through the target, reaching as far as the radius unless a wall
+    /// stops it
+    Line,
+}
+
+impl AreaShape {
+    pub fn label(&self) -> &'static str {
+        match self {
+            AreaShape::Circle => "Circle",
+            AreaShape::Cone => "Cone",
+            AreaShape::Line => "Line",
+        }
+    }
+}
+
+/// Flag component for items that share an inventory slot with other items of the same name
 #[derive(Component, Debug, Clone)]
+pub struct Stackable {}
+
+pub const UNKNOWN_ITEM_NAME: &str = "<NOT IMPLEMENTED>";
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct ItemName {
     pub name: String,
 }
 
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
 pub struct Confusion {
     pub turns: u32,
 }
@@ -1,7 +1,12 @@
 pub mod collidable;
 pub mod combat_stats;
 pub mod consumable;
+pub mod corpse;
 pub mod damage;
+pub mod equipment;
+pub mod experience;
+pub mod glyph;
+pub mod hunger;
 pub mod item;
 pub mod position;
 pub mod user_input;
@@ -1,7 +1,8 @@
 use bevy::ecs::component::Component;
 use pathfinding::num_traits::abs;
+use serde::{Deserialize, Serialize};
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug, Component)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Component, Serialize, Deserialize)]
 pub struct Position {
     pub x: i32,
     pub y: i32,
@@ -22,6 +23,11 @@ impl Position {
         ((a.pow(2) + b.pow(2)) as f32).sqrt() as i32
     }
 
+    /// Gets the number of steps needed to reach the destination when diagonal moves are allowed
+    pub fn get_step_distance(&self, dest: &Position) -> i32 {
+        (self.x - dest.x).abs().max((self.y - dest.y).abs())
+    }
+
     pub fn is_adjacent_to(&self, other_pos: &Position) -> bool {
         if self == other_pos {
             return false;
@@ -54,6 +60,15 @@ fn can_get_airline_distance() {
     assert_eq!(a.get_airline_distance(&d), 3);
 }
 
+#[test]
+fn can_get_step_distance() {
+    let a = Position::new(1, 1);
+
+    assert_eq!(a.get_step_distance(&Position::new(2, 2)), 1);
+    assert_eq!(a.get_step_distance(&Position::new(4, 4)), 3);
+    assert_eq!(a.get_step_distance(&Position::new(4, 2)), 3);
+}
+
 #[test]
 fn can_determine_adjacents() {
     let point = Position::new(0, 0);
@@ -1,4 +1,4 @@
-use bevy::prelude::Resource;
+use bevy::prelude::{warn, Resource};
 
 #[derive(Resource, Default, Debug)]
 pub struct GameConfig {
@@ -6,6 +6,7 @@ pub struct GameConfig {
     pub screen_dimensions: ScreenDimensions,
     pub map_properties: MapProperties,
     pub gameplay_settings: GameplaySettings,
+    pub leveling_settings: LevelingSettings,
 }
 
 pub const SCREEN_HEIGHT: f32 = 720.0;
@@ -24,11 +25,12 @@ pub struct TileProperties {
     pub player_z: f32,
     pub monster_z: f32,
     pub item_z: f32,
+    pub corpse_z: f32,
 }
 
 impl TileProperties {
     pub fn get_scaled_tile_size(&self) -> f32 {
-        return self.tile_scale * self.tile_size;
+        self.tile_scale * self.tile_size
     }
 }
 
@@ -37,11 +39,51 @@ pub struct MapProperties {
     pub map_height: i32,
     pub map_width: i32,
     pub max_rooms: u32,
+    /// Seed given with `--seed`. Every new game reuses it, without one each game gets a random
+    /// seed.
+    pub seed: Option<u64>,
+}
+
+/// Reads the map seed from the command line (`--seed <number>`), if one was given.
+pub fn parse_seed_arg(args: &[String]) -> Option<u64> {
+    let seed_flag_pos = args.iter().position(|arg| arg == "--seed")?;
+
+    match args.get(seed_flag_pos + 1).map(|seed| seed.parse::<u64>()) {
+        Some(Ok(seed)) => Some(seed),
+        _ => {
+            warn!("--seed expects an unsigned number, falling back to a random seed");
+            None
+        }
+    }
 }
 
 #[derive(Default, Debug)]
 pub struct GameplaySettings {
     pub player_starting_health: i32,
-    pub health_potion_heal_amount: i32,
-    pub monster_starting_health: i32,
+}
+
+/// How the player grows stronger by gaining experience
+#[derive(Default, Debug)]
+pub struct LevelingSettings {
+    /// Total xp needed for each level, starting with level 2
+    pub xp_thresholds: Vec<i32>,
+    pub max_hp_per_level: i32,
+    pub power_per_level: i32,
+    pub defense_per_level: i32,
+}
+
+#[test]
+fn can_parse_seed_arg() {
+    let to_args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+
+    assert_eq!(
+        parse_seed_arg(&to_args(&["roguelike", "--seed", "42"])),
+        Some(42)
+    );
+    assert_eq!(parse_seed_arg(&to_args(&["roguelike"])), None);
+    assert_eq!(parse_seed_arg(&to_args(&["roguelike", "--seed"])), None);
+    assert_eq!(
+        parse_seed_arg(&to_args(&["roguelike", "--seed", "abc"])),
+        None
+    );
 }
@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{warn, Input, KeyCode, Resource};
+use serde::{Deserialize, Serialize};
+
+pub const KEY_BINDINGS_PATH: &str = "assets/config/key_bindings.ron";
+
+/// Everything the player can do with the keyboard
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum InputAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    Wait,
+    /// Waits turn after turn until healed or interrupted
+    Rest,
+    PickUp,
+    Descend,
+    AutoExplore,
+    Inventory,
+    UseItem,
+    DropItem,
+    /// Opens the main menu, or backs out of the current menu or targeting
+    Cancel,
+    Confirm,
+    /// Cycles through the monsters in range while targeting
+    NextTarget,
+    NewGame,
+    Quit,
+}
+
+impl InputAction {
+    pub const MOVEMENT: [InputAction; 8] = [
+        InputAction::MoveUp,
+        InputAction::MoveDown,
+        InputAction::MoveLeft,
+        InputAction::MoveRight,
+        InputAction::MoveUpLeft,
+        InputAction::MoveUpRight,
+        InputAction::MoveDownLeft,
+        InputAction::MoveDownRight,
+    ];
+
+    /// The direction a movement action moves in, (0, 0) for everything else
+    pub fn direction(&self) -> (i32, i32) {
+        match self {
+            InputAction::MoveUp => (0, 1),
+            InputAction::MoveDown => (0, -1),
+            InputAction::MoveLeft => (-1, 0),
+            InputAction::MoveRight => (1, 0),
+            InputAction::MoveUpLeft => (-1, 1),
+            InputAction::MoveUpRight => (1, 1),
+            InputAction::MoveDownLeft => (-1, -1),
+            InputAction::MoveDownRight => (1, -1),
+            _ => (0, 0),
+        }
+    }
+}
+
+/// Maps every action to the keys that trigger it
+#[derive(Resource, Serialize, Deserialize, PartialEq, Debug)]
+pub struct KeyBindings {
+    pub bindings: HashMap<InputAction, Vec<KeyCode>>,
+    /// Keys using the item in the inventory slot of the same index directly. They only apply on
+    /// the inventory screen, so they may reuse keys bound to actions elsewhere.
+    #[serde(default)]
+    pub inventory_hotkeys: Vec<KeyCode>,
+}
+
+impl Default for KeyBindings {
+    /// WASD with QEZC for diagonals, arrow keys, numpad and vi-keys. No key is bound to more
+    /// than one action, so none of them can shadow another one.
+    fn default() -> Self {
+        use InputAction::*;
+        use KeyCode::*;
+
+        let bindings = HashMap::from([
+            (MoveUp, vec![W, Up, Numpad8, K]),
+            (MoveDown, vec![S, Down, Numpad2, J]),
+            (MoveLeft, vec![A, Left, Numpad4, H]),
+            (MoveRight, vec![D, Right, Numpad6, L]),
+            (MoveUpLeft, vec![Q, Numpad7, Y]),
+            (MoveUpRight, vec![E, Numpad9, U]),
+            (MoveDownLeft, vec![Z, Numpad1, B]),
+            (MoveDownRight, vec![C, Numpad3, N]),
+            (Wait, vec![Space, Numpad5]),
+            (Rest, vec![R]),
+            (PickUp, vec![G]),
+            (Descend, vec![Period]),
+            (AutoExplore, vec![O]),
+            (Inventory, vec![I]),
+            (UseItem, vec![F]),
+            (DropItem, vec![X]),
+            (Cancel, vec![Escape]),
+            (Confirm, vec![Return, NumpadEnter]),
+            (NextTarget, vec![Tab]),
+            (NewGame, vec![F2]),
+            (Quit, vec![F10]),
+        ]);
+
+        let inventory_hotkeys = vec![
+            A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        ];
+
+        KeyBindings {
+            bindings,
+            inventory_hotkeys,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Loads the key bindings from disk. Actions the file does not mention keep their default
+    /// keys, as do the inventory hotkeys if the file leaves them out. A missing or broken file
+    /// falls back to the defaults entirely.
+    pub fn load(path: &str) -> KeyBindings {
+        let mut key_bindings = KeyBindings::default();
+
+        let loaded = match std::fs::read_to_string(path) {
+            Ok(serialized) => KeyBindings::from_ron(&serialized),
+            Err(_) => return key_bindings,
+        };
+        match loaded {
+            Ok(loaded) => {
+                key_bindings.bindings.extend(loaded.bindings);
+                if !loaded.inventory_hotkeys.is_empty() {
+                    key_bindings.inventory_hotkeys = loaded.inventory_hotkeys;
+                }
+            }
+            Err(e) => warn!(
+                "Failed to parse key bindings {}, using the defaults: {}",
+                path, e
+            ),
+        }
+
+        key_bindings
+    }
+
+    pub fn from_ron(key_bindings: &str) -> Result<KeyBindings, ron::Error> {
+        ron::de::from_str(key_bindings)
+    }
+
+    pub fn keys(&self, action: InputAction) -> &[KeyCode] {
+        self.bindings
+            .get(&action)
+            .map(|keys| keys.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn just_pressed(&self, keyboard_input: &Input<KeyCode>, action: InputAction) -> bool {
+        keyboard_input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// Name of the first key bound to an action, for showing it in the UI
+    pub fn key_label(&self, action: InputAction) -> String {
+        self.keys(action)
+            .first()
+            .map(|key| format!("{:?}", key))
+            .unwrap_or_else(|| "unbound".to_owned())
+    }
+}
+
+#[test]
+fn shipped_key_bindings_match_defaults() {
+    let key_bindings =
+        KeyBindings::from_ron(include_str!("../../assets/config/key_bindings.ron")).unwrap();
+
+    assert_eq!(key_bindings, KeyBindings::default());
+}
+
+/// The inventory hotkeys are a key context of their own and are left out on purpose
+#[test]
+fn default_keys_trigger_a_single_action() {
+    let key_bindings = KeyBindings::default();
+
+    let mut bound_keys = std::collections::HashSet::new();
+    for keys in key_bindings.bindings.values() {
+        for key in keys {
+            assert!(bound_keys.insert(*key), "{:?} is bound twice", key);
+        }
+    }
+}
+
+#[test]
+fn partial_key_bindings_only_override_listed_actions() {
+    let path = std::env::temp_dir().join("roguelike_partial_key_bindings.ron");
+    std::fs::write(&path, "(bindings: { Wait: [Period], Quit: [Escape] })").unwrap();
+
+    let key_bindings = KeyBindings::load(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+
+    let defaults = KeyBindings::default();
+    assert_eq!(key_bindings.inventory_hotkeys, defaults.inventory_hotkeys);
+    assert_eq!(key_bindings.keys(InputAction::Wait), &[KeyCode::Period]);
+    assert_eq!(key_bindings.keys(InputAction::Quit), &[KeyCode::Escape]);
+    for (action, keys) in defaults.bindings.iter() {
+        if *action != InputAction::Wait && *action != InputAction::Quit {
+            assert_eq!(key_bindings.keys(*action), keys.as_slice());
+        }
+    }
+}
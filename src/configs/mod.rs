@@ -1 +1,2 @@
 pub mod game_settings;
+pub mod key_bindings;
@@ -1,15 +1,24 @@
 use bevy::prelude::*;
 
-use crate::components::{combat_stats::CombatStats, damage::DamageTracker};
-use crate::monster::MONSTER_TURN_LABEL;
+use crate::components::{
+    combat_stats::CombatStats,
+    damage::DamageTracker,
+    experience::{Experience, ExperienceReward},
+};
+use crate::game_over::GameStats;
+use crate::monster::{MonsterName, MONSTER_TURN_LABEL, UNKNOWN_MONSTER_NAME};
 use crate::player::{Player, PLAYER_TURN_LABEL};
+use crate::raws::Raws;
+use crate::spawner::{spawn_corpse, spawn_named_item};
 use crate::user_interface::ActionLog;
-use crate::GameState;
+use crate::utils::rng::GameRng;
 use crate::{components::position::Position, map::game_map::GameMap};
+use crate::{GameConfig, GameState};
 
 pub struct DamageSystemPlugin {}
 
-const APPLY_DAMAGE_LABEL: &str = "apply_damage";
+pub const APPLY_DAMAGE_LABEL: &str = "apply_damage";
+pub const COLLECT_DEAD_LABEL: &str = "collect_dead";
 
 impl Plugin for DamageSystemPlugin {
     fn build(&self, app: &mut App) {
@@ -23,17 +32,20 @@ impl Plugin for DamageSystemPlugin {
         app.add_system_set(
             SystemSet::on_update(GameState::PlayerTurn)
                 .with_system(collect_dead)
-                .after(APPLY_DAMAGE_LABEL),
+                .after(APPLY_DAMAGE_LABEL)
+                .label(COLLECT_DEAD_LABEL),
         );
         app.add_system_set(
             SystemSet::on_update(GameState::MonsterTurn)
                 .with_system(apply_damage)
-                .after(MONSTER_TURN_LABEL),
+                .after(MONSTER_TURN_LABEL)
+                .label(APPLY_DAMAGE_LABEL),
         );
         app.add_system_set(
             SystemSet::on_update(GameState::MonsterTurn)
                 .with_system(collect_dead)
-                .after(APPLY_DAMAGE_LABEL),
+                .after(APPLY_DAMAGE_LABEL)
+                .label(COLLECT_DEAD_LABEL),
         );
     }
 }
@@ -50,31 +62,126 @@ fn apply_damage(
     }
 }
 
+/// Removes everything that ran out of hp. Dead monsters leave a corpse behind and may drop loot
+/// from their loot table and grant their xp to whoever dealt the killing blow. The player dying
+/// ends the game (`GameState::GameOver`).
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn collect_dead(
     mut commands: Commands,
+    mut app_state: ResMut<State<GameState>>,
     mut map: ResMut<GameMap>,
     player_entity: Query<Entity, With<Player>>,
-    combat_stats_query: Query<(Entity, &Position, &CombatStats)>,
+    combat_stats_query: Query<(
+        Entity,
+        &Position,
+        &CombatStats,
+        Option<&MonsterName>,
+        Option<&Sprite>,
+        Option<&ExperienceReward>,
+    )>,
+    mut experience_query: Query<&mut Experience>,
     mut action_log: ResMut<ActionLog>,
+    mut damage_tracker: ResMut<DamageTracker>,
+    mut game_stats: ResMut<GameStats>,
+    mut game_rng: ResMut<GameRng>,
+    raws: Res<Raws>,
+    game_config: Res<GameConfig>,
 ) {
-    for (entity, position, combat_stats) in combat_stats_query.iter() {
+    for (entity, position, combat_stats, monster_name, sprite, reward) in combat_stats_query.iter()
+    {
         if combat_stats.hp <= 0 {
             let player = player_entity
                 .get_single()
                 .expect("Found 0 or more than one player in collect_dead");
 
-            let text = if player == entity {
-                "You died!"
-            } else {
-                "Monster died"
-            };
+            let last_damage = damage_tracker.0.remove(&entity);
+
+            if player == entity {
+                action_log.entries.push("You died!".to_owned());
+                game_stats.cause_of_death = last_damage.map(|damage| damage.last_attacker);
+
+                // Whatever turn was about to come next, the game is over
+                app_state
+                    .overwrite_set(GameState::GameOver)
+                    .expect("failed to set game state after the player died");
+                continue;
+            }
 
-            action_log.entries.push(text.to_owned());
+            let name = monster_name
+                .map(|name| name.name.as_str())
+                .unwrap_or(UNKNOWN_MONSTER_NAME);
+            action_log.entries.push(format!("{} died", name));
+            game_stats.kills += 1;
 
-            commands.entity(entity).despawn();
+            let killer = last_damage.and_then(|damage| damage.last_attacker_entity);
+            if let (Some(reward), Some(killer)) = (reward, killer) {
+                if let Ok(mut experience) = experience_query.get_mut(killer) {
+                    experience.xp += reward.xp;
+                }
+            }
+
+            if let Some(monster_name) = monster_name {
+                spawn_monster_remains(
+                    &mut commands,
+                    &monster_name.name,
+                    sprite.map(|sprite| sprite.color).unwrap_or(Color::GRAY),
+                    position,
+                    &mut game_rng,
+                    &raws,
+                    &game_config,
+                    &mut action_log,
+                );
+            }
+
+            commands.entity(entity).despawn_recursive();
 
             map.remove_blocked(position);
             map.remove_tile_content(position);
         }
     }
 }
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_monster_remains(
+    commands: &mut Commands,
+    monster_name: &str,
+    monster_color: Color,
+    position: &Position,
+    game_rng: &mut GameRng,
+    raws: &Raws,
+    game_config: &GameConfig,
+    action_log: &mut ActionLog,
+) {
+    spawn_corpse(
+        commands,
+        monster_name,
+        monster_color,
+        position,
+        &game_config.tile_properties,
+        &game_config.screen_dimensions,
+    );
+
+    let loot = raws
+        .get_monster(monster_name)
+        .and_then(|monster| monster.loot.as_ref())
+        .and_then(|loot| loot.roll(&mut game_rng.rng));
+
+    if let Some(item_name) = loot {
+        let dropped = spawn_named_item(
+            commands,
+            raws,
+            item_name,
+            position,
+            &game_config.tile_properties,
+            &game_config.screen_dimensions,
+        );
+
+        if dropped.is_some() {
+            action_log.entries.push(format!(
+                "The {} drops a {}",
+                monster_name.to_lowercase(),
+                item_name
+            ));
+        }
+    }
+}
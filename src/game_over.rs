@@ -0,0 +1,211 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::damage::DamageTracker,
+    configs::{
+        game_settings::GameConfig,
+        key_bindings::{InputAction, KeyBindings},
+    },
+    main_menu::reset_running_game,
+    map::game_map::GameMap,
+    spawner::GameEntityFilter,
+    user_interface::{ActionLog, UIFont},
+    utils::rng::GameRng,
+    GameState,
+};
+
+const BUTTON_COLOR: Color = Color::PINK;
+const BUTTON_HOVERED_COLOR: Color = Color::PURPLE;
+
+pub struct GameOverPlugin {}
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameStats>();
+        app.add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(generate_death_screen)
+                .label("death_screen_setup"),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameState::GameOver)
+                .with_system(death_screen_system)
+                .after("death_screen_setup"),
+        );
+    }
+}
+
+/// Numbers about the running game that are shown on the death screen
+#[derive(Resource, Default, Debug, Clone, Serialize, Deserialize)]
+pub struct GameStats {
+    pub turns: u32,
+    pub kills: u32,
+    /// Name of whatever killed the player
+    pub cause_of_death: Option<String>,
+}
+
+#[derive(Component)]
+pub struct DeathScreenUI {}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathScreenEntry {
+    NewGame,
+    Quit,
+}
+
+#[derive(Component)]
+pub struct DeathScreenButton {
+    pub entry: DeathScreenEntry,
+}
+
+fn generate_death_screen(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    game_stats: Res<GameStats>,
+    map: Res<GameMap>,
+    ui_font: Res<UIFont>,
+    key_bindings: Res<KeyBindings>,
+) {
+    let cause = game_stats.cause_of_death.as_deref().unwrap_or("something");
+    let recap = format!(
+        "You died!\n\nKilled by {} on depth {}\nSurvived {} turns\nKilled {} monsters",
+        cause, map.depth, game_stats.turns, game_stats.kills
+    );
+
+    let text_style = TextStyle {
+        font: ui_font.0.clone(),
+        font_size: 27.0,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                size: Size::new(
+                    Val::Px(game_config.screen_dimensions.screen_width),
+                    Val::Px(game_config.screen_dimensions.screen_height),
+                ),
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            ..Default::default()
+        })
+        .insert(DeathScreenUI {})
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Percent(15.0),
+                        left: Val::Percent(33.3),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                text: Text::from_section(recap, text_style.clone()),
+                ..Default::default()
+            });
+
+            let entries = [
+                (
+                    DeathScreenEntry::NewGame,
+                    format!(
+                        "New Game ({})",
+                        key_bindings.key_label(InputAction::NewGame)
+                    ),
+                    33.3,
+                ),
+                (
+                    DeathScreenEntry::Quit,
+                    format!("Quit ({})", key_bindings.key_label(InputAction::Quit)),
+                    15.0,
+                ),
+            ];
+            for (entry, label, bottom) in entries {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Percent(33.3), Val::Percent(10.0)),
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                bottom: Val::Percent(bottom),
+                                left: Val::Percent(33.3),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                        background_color: BUTTON_COLOR.into(),
+                        ..Default::default()
+                    })
+                    .insert(DeathScreenButton { entry })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle {
+                            text: Text::from_section(label, text_style.clone()),
+                            ..Default::default()
+                        });
+                    });
+            }
+        });
+}
+
+/// Starts a new game or quits on the matching key. The buttons can be clicked as well.
+#[allow(clippy::too_many_arguments)]
+fn death_screen_system(
+    mut app_state: ResMut<State<GameState>>,
+    mut commands: Commands,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    death_screen_query: Query<Entity, With<DeathScreenUI>>,
+    mut interaction_query: Query<
+        (&Interaction, &DeathScreenButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    game_entities_query: Query<Entity, GameEntityFilter>,
+    mut damage_tracker: ResMut<DamageTracker>,
+    mut action_log: ResMut<ActionLog>,
+    mut game_stats: ResMut<GameStats>,
+    mut game_rng: ResMut<GameRng>,
+    mut app_exit: EventWriter<AppExit>,
+    key_bindings: Res<KeyBindings>,
+    game_config: Res<GameConfig>,
+) {
+    let mut selected = None;
+    if key_bindings.just_pressed(&keyboard_input, InputAction::NewGame) {
+        selected = Some(DeathScreenEntry::NewGame);
+    } else if key_bindings.just_pressed(&keyboard_input, InputAction::Quit) {
+        selected = Some(DeathScreenEntry::Quit);
+    }
+    keyboard_input.clear();
+
+    for (interaction, button, mut color) in interaction_query.iter_mut() {
+        match interaction {
+            Interaction::Clicked => selected = Some(button.entry),
+            Interaction::Hovered => color.0 = BUTTON_HOVERED_COLOR,
+            Interaction::None => color.0 = BUTTON_COLOR,
+        }
+    }
+
+    match selected {
+        Some(DeathScreenEntry::NewGame) => {
+            for death_screen in death_screen_query.iter() {
+                commands.entity(death_screen).despawn_recursive();
+            }
+            reset_running_game(
+                &mut commands,
+                &game_entities_query,
+                &mut damage_tracker,
+                &mut action_log,
+                &mut game_stats,
+                &mut game_rng,
+                game_config.map_properties.seed,
+            );
+
+            app_state
+                .set(GameState::LoadingResources)
+                .expect("failed to set game state for a new game");
+        }
+        Some(DeathScreenEntry::Quit) => app_exit.send(AppExit),
+        None => {}
+    }
+}
@@ -0,0 +1,60 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{
+        damage::{DamageTracker, SufferDamage},
+        hunger::{HungerClock, HungerState},
+    },
+    damage_system::APPLY_DAMAGE_LABEL,
+    player::{Player, PLAYER_TURN_LABEL},
+    user_interface::ActionLog,
+    GameState,
+};
+
+/// Damage taken every turn while starving
+const STARVATION_DAMAGE: i32 = 1;
+const STARVATION_NAME: &str = "starvation";
+
+pub struct HungerSystemPlugin {}
+
+impl Plugin for HungerSystemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::PlayerTurn)
+                .with_system(hunger_clock)
+                .after(PLAYER_TURN_LABEL)
+                .before(APPLY_DAMAGE_LABEL),
+        );
+    }
+}
+
+/// Makes the player hungrier every turn. Starving players take damage until they eat something.
+fn hunger_clock(
+    mut player_query: Query<(Entity, &mut HungerClock), With<Player>>,
+    mut damage_tracker: ResMut<DamageTracker>,
+    mut action_log: ResMut<ActionLog>,
+) {
+    let (player, mut hunger_clock) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    match hunger_clock.tick() {
+        Some(HungerState::Normal) => action_log
+            .entries
+            .push("You are no longer well fed".to_owned()),
+        Some(HungerState::Hungry) => action_log.entries.push("You are hungry".to_owned()),
+        Some(HungerState::Starving) => action_log.entries.push("You are starving!".to_owned()),
+        _ => {}
+    }
+
+    if hunger_clock.state == HungerState::Starving {
+        SufferDamage::record_damage(
+            &mut damage_tracker,
+            player,
+            STARVATION_DAMAGE,
+            None,
+            STARVATION_NAME,
+        );
+    }
+}
@@ -1,13 +1,41 @@
 use bevy::prelude::{Component, Entity};
 
-use crate::components::position::Position;
-/// Component that holds a vector of items per item type. Used by the inventory plugin.
+use crate::components::{
+    equipment::{Equipment, EquipmentSlot},
+    position::Position,
+};
+
+/// Component that holds a vector of item stacks, one per inventory slot. Used by the inventory
+/// plugin.
 #[derive(Component)]
 pub struct Inventory {
-    pub items: Vec<Option<Entity>>,
+    pub items: Vec<Option<ItemStack>>,
     pub inventory_size: usize,
 }
-#[derive(PartialEq)]
+
+/// Items sharing a single inventory slot. Only items with the same stack key end up in the same
+/// stack, items without a stack key always get a slot of their own.
+#[derive(Debug, Clone)]
+pub struct ItemStack {
+    pub stack_key: Option<String>,
+    pub entities: Vec<Entity>,
+}
+
+impl ItemStack {
+    /// The item that gets used or dropped next
+    pub fn top(&self) -> Entity {
+        *self
+            .entities
+            .last()
+            .expect("Inventory stacks are never empty")
+    }
+
+    pub fn count(&self) -> usize {
+        self.entities.len()
+    }
+}
+
+#[derive(PartialEq, Debug)]
 pub enum InventoryError {
     InventoryFull,
 }
@@ -25,36 +53,91 @@ impl Inventory {
         }
     }
 
-    pub fn add_item(&mut self, item: Entity) -> Result<(), InventoryError> {
+    /// Adds the item to the stack with the same `stack_key` or to the first free slot
+    pub fn add_item(
+        &mut self,
+        item: Entity,
+        stack_key: Option<&str>,
+    ) -> Result<(), InventoryError> {
+        if let Some(key) = stack_key {
+            for stack in self.items.iter_mut().flatten() {
+                if stack.stack_key.as_deref() == Some(key) {
+                    stack.entities.push(item);
+                    return Ok(());
+                }
+            }
+        }
+
         for i in 0..self.inventory_size {
             if self.items[i].is_none() {
-                self.items[i] = Some(item);
+                self.items[i] = Some(ItemStack {
+                    stack_key: stack_key.map(|key| key.to_owned()),
+                    entities: vec![item],
+                });
                 return Ok(());
             }
         }
 
-        return Err(InventoryError::InventoryFull);
+        Err(InventoryError::InventoryFull)
     }
 
+    /// Takes the item out of its stack. The slot is freed once the stack is empty.
     pub fn remove_item_by_entity(&mut self, target_entity: Entity) {
-        let mut slot_to_empty: Option<usize> = None;
-        for (slot, item) in self.items.iter().enumerate() {
-            if let Some(item_entity) = item {
-                if *item_entity == target_entity {
-                    slot_to_empty = Some(slot);
+        for slot in self.items.iter_mut() {
+            if let Some(stack) = slot {
+                stack.entities.retain(|entity| *entity != target_entity);
+
+                if stack.entities.is_empty() {
+                    *slot = None;
                 }
             }
         }
+    }
+
+    /// The item that gets used or dropped next from the given slot
+    pub fn get_item(&self, slot: usize) -> Option<Entity> {
+        self.items[slot].as_ref().map(|stack| stack.top())
+    }
+
+    /// Moves the item from the inventory into its equipment slot. Whatever was worn in that slot
+    /// before is put into the inventory instead and returned.
+    pub fn equip_item(
+        &mut self,
+        equipment: &mut Equipment,
+        item: Entity,
+        slot: EquipmentSlot,
+    ) -> Result<Option<Entity>, InventoryError> {
+        let frees_slot = self
+            .items
+            .iter()
+            .flatten()
+            .any(|stack| stack.entities == [item]);
+        let has_free_slot = self.items.iter().any(|slot| slot.is_none());
+        if equipment.get(slot).is_some() && !frees_slot && !has_free_slot {
+            return Err(InventoryError::InventoryFull);
+        }
 
-        if let Some(slot) = slot_to_empty {
-            self.items[slot] = None
+        self.remove_item_by_entity(item);
+        let replaced = equipment.equip(slot, item);
+        if let Some(replaced) = replaced {
+            self.add_item(replaced, None)?;
         }
+
+        Ok(replaced)
     }
-}
 
-/// Flag component for marking the player's inventory.
-#[derive(Component)]
-pub struct PlayerInventory {}
+    /// Takes the worn item off and puts it into the inventory
+    pub fn unequip_item(
+        &mut self,
+        equipment: &mut Equipment,
+        item: Entity,
+    ) -> Result<(), InventoryError> {
+        self.add_item(item, None)?;
+        equipment.unequip(item);
+
+        Ok(())
+    }
+}
 
 /// Flag component marking the root frame that all UI elements are children of.
 #[derive(Component)]
@@ -64,6 +147,18 @@ pub struct InventoryUIRoot {}
 #[derive(Component)]
 pub struct InventoryUISlot {}
 
+/// Flag component marking the text showing how many items are stacked in a slot.
+#[derive(Component)]
+pub struct InventoryUISlotCount {}
+
+/// Flag component marking the text next to a slot, showing its hotkey and the item's name.
+#[derive(Component)]
+pub struct InventoryUISlotLabel {}
+
+/// Flag component marking the panel describing the item in the selected slot.
+#[derive(Component)]
+pub struct InventoryUIDescription {}
+
 /// Flag component marking the frame for a slot. Used by the inventory cursor to highlight
 /// current selection.
 #[derive(Component)]
@@ -109,3 +204,77 @@ pub struct WantsToUseItem {
     pub entity: Entity,
     pub targets: Option<Vec<Position>>,
 }
+
+/// Component that flags an item in the inventory to be put down at the player's position.
+#[derive(Component)]
+pub struct WantsToDropItem {
+    pub entity: Entity,
+}
+
+/// Component that flags an item in the inventory to be equipped, or unequipped if it is already
+/// worn.
+#[derive(Component)]
+pub struct WantsToEquipItem {
+    pub entity: Entity,
+}
+
+#[test]
+fn can_stack_items() {
+    let mut inventory = Inventory::new(2);
+    let potions = [Entity::from_raw(1), Entity::from_raw(2)];
+    let dagger = Entity::from_raw(3);
+    let other_dagger = Entity::from_raw(4);
+
+    inventory
+        .add_item(potions[0], Some("Health Potion"))
+        .unwrap();
+    inventory.add_item(dagger, None).unwrap();
+    inventory
+        .add_item(potions[1], Some("Health Potion"))
+        .unwrap();
+    assert_eq!(inventory.items[0].as_ref().unwrap().count(), 2);
+    assert_eq!(inventory.get_item(0), Some(potions[1]));
+
+    // Unstackable items need a free slot
+    assert_eq!(
+        inventory.add_item(other_dagger, None),
+        Err(InventoryError::InventoryFull)
+    );
+
+    inventory.remove_item_by_entity(potions[1]);
+    assert_eq!(inventory.items[0].as_ref().unwrap().count(), 1);
+    inventory.remove_item_by_entity(potions[0]);
+    assert!(inventory.items[0].is_none());
+    assert_eq!(inventory.get_item(1), Some(dagger));
+}
+
+#[test]
+fn equipping_swaps_with_the_worn_item() {
+    let mut inventory = Inventory::new(1);
+    let mut equipment = Equipment::default();
+    let dagger = Entity::from_raw(1);
+    let longsword = Entity::from_raw(2);
+
+    inventory.add_item(dagger, None).unwrap();
+    assert_eq!(
+        inventory.equip_item(&mut equipment, dagger, EquipmentSlot::Weapon),
+        Ok(None)
+    );
+    // Worn items don't take up inventory space
+    assert!(inventory.items[0].is_none());
+
+    inventory.add_item(longsword, None).unwrap();
+    assert_eq!(
+        inventory.equip_item(&mut equipment, longsword, EquipmentSlot::Weapon),
+        Ok(Some(dagger))
+    );
+    assert_eq!(equipment.get(EquipmentSlot::Weapon), Some(longsword));
+    assert_eq!(inventory.get_item(0), Some(dagger));
+
+    // Nowhere to put the longsword when taking it off
+    assert_eq!(
+        inventory.unequip_item(&mut equipment, longsword),
+        Err(InventoryError::InventoryFull)
+    );
+    assert!(equipment.is_worn(longsword));
+}
@@ -1,7 +1,8 @@
 use bevy::prelude::{App, Plugin, SystemSet};
 
 use super::systems::{
-    inventory_renderer, inventory_setup, pickup_handler, use_item_handler, user_input_handler,
+    drop_item_handler, equip_item_handler, inventory_renderer, inventory_setup, pickup_handler,
+    use_item_handler, user_input_handler,
 };
 use crate::GameState;
 
@@ -14,6 +15,12 @@ impl Plugin for InventorySystemPlugin {
         app.add_system_set(
             SystemSet::on_update(GameState::PlayerTurn).with_system(use_item_handler),
         );
+        app.add_system_set(
+            SystemSet::on_update(GameState::PlayerTurn).with_system(equip_item_handler),
+        );
+        app.add_system_set(
+            SystemSet::on_update(GameState::PlayerTurn).with_system(drop_item_handler),
+        );
         app.add_system_set(
             SystemSet::on_update(GameState::AwaitingInventoryInput).with_system(user_input_handler),
         );
@@ -7,30 +7,57 @@ use crate::{
         combat_stats::CombatStats,
         consumable::Consumable,
         damage::{DamageTracker, InflictsDamage, SufferDamage},
-        item::{Confusion, Heals, Item, Ranged},
+        equipment::{Equipment, EquipmentSlot, Equippable},
+        hunger::{HungerClock, ProvidesFood},
+        item::{
+            AreaOfEffect, AreaShape, Confusion, Heals, Item, ItemName, Ranged, Stackable,
+            UNKNOWN_ITEM_NAME,
+        },
         position::Position,
     },
-    map::game_map::GameMap,
-    player::Player,
-    user_interface::{ActionLog, ActionLogText, HealthBar, HealthText, TargetingModeContext},
+    configs::key_bindings::{InputAction, KeyBindings},
+    monster::{MonsterName, UNKNOWN_MONSTER_NAME},
+    player::{Player, PLAYER_NAME},
+    spawner::item_transform,
+    user_interface::{
+        ActionLog, ActionLogText, HealthBar, HealthText, TargetingModeContext, UIFont,
+    },
     utils::input_utils::get_movement_input,
-    GameState,
+    GameConfig, GameState,
 };
 
 use super::components::{
-    Inventory, InventoryCursor, InventoryError, InventoryUIRoot, InventoryUISlot,
-    InventoryUISlotFrame, WantsToPickupItem, WantsToUseItem,
+    Inventory, InventoryCursor, InventoryError, InventoryUIDescription, InventoryUIRoot,
+    InventoryUISlot, InventoryUISlotCount, InventoryUISlotFrame, InventoryUISlotLabel,
+    WantsToDropItem, WantsToEquipItem, WantsToPickupItem, WantsToUseItem,
 };
 
 const UNKNOWN_ITEM_COLOR: Color = Color::YELLOW;
 const EMPTY_SLOT_COLOR: Color = Color::GRAY;
+const CURSOR_FRAME_COLOR: Color = Color::WHITE;
+const EQUIPPED_FRAME_COLOR: Color = Color::GOLD;
+const SLOT_FRAME_COLOR: Color = Color::GRAY;
+const SLOT_SIZE_PX: f32 = 60.0;
+const SLOT_GAP_PX: f32 = 15.0;
+
+type ItemDescriptionQueryItem<'a> = (
+    Option<&'a Heals>,
+    Option<&'a InflictsDamage>,
+    Option<&'a Ranged>,
+    Option<&'a AreaOfEffect>,
+    Option<&'a AreaShape>,
+    Option<&'a Confusion>,
+    Option<&'a Equippable>,
+    Option<&'a Consumable>,
+    Option<&'a ProvidesFood>,
+);
 
 /// System for processing a pickup action by the user. Removes the item in question from the map
 /// and adds it to the player inventory.
 pub fn pickup_handler(
     mut commands: Commands,
     pickup_query: Query<(Entity, &WantsToPickupItem)>,
-    mut visiblity_query: Query<&mut Visibility, With<Item>>,
+    mut visiblity_query: Query<(&mut Visibility, Option<&Stackable>), With<Item>>,
     mut player_inventory_query: Query<&mut Inventory, With<Player>>,
     mut action_log: ResMut<ActionLog>,
 ) {
@@ -39,42 +66,48 @@ pub fn pickup_handler(
         .expect("We don't have exactly one inventory!!11");
 
     for (pickup_attempt_entity, pickup_attempt) in pickup_query.iter() {
-        match visiblity_query.get_mut(pickup_attempt.entity) {
-            Ok(mut vis) => vis.is_visible = false,
-            Err(e) => bevy::log::error!("{}", e),
+        commands.entity(pickup_attempt_entity).despawn();
+
+        let (mut vis, stackable) = match visiblity_query.get_mut(pickup_attempt.entity) {
+            Ok(item) => item,
+            Err(e) => {
+                bevy::log::error!("{}", e);
+                continue;
+            }
+        };
+
+        let stack_key = stackable.map(|_| pickup_attempt.item_name.as_str());
+        if let Err(InventoryError::InventoryFull) =
+            player_inv.add_item(pickup_attempt.entity, stack_key)
+        {
+            action_log.entries.push("Inventory is full!".to_owned());
+            continue;
         }
+
         // remove item from map
+        vis.is_visible = false;
         commands.entity(pickup_attempt.entity).remove::<Transform>();
         commands.entity(pickup_attempt.entity).remove::<Position>();
 
-        if let Err(err) = player_inv.add_item(pickup_attempt.entity) {
-            if err == InventoryError::InventoryFull {
-                action_log.entries.push("Inventory is full!".to_owned());
-                return;
-            }
-
-            todo!("Unimplemented error case!")
-        }
-
         action_log
             .entries
             .push(format!("Picked up {}", pickup_attempt.item_name));
-
-        commands.entity(pickup_attempt_entity).despawn();
     }
 }
 
 /// System processing player input while in the inventory. Responsible for moving the cursor for the selected
 /// item slot, using items and closing the inventory.
+#[allow(clippy::too_many_arguments)]
 pub fn user_input_handler(
     mut keyboard_input: ResMut<Input<KeyCode>>,
     mut app_state: ResMut<State<GameState>>,
     mut commands: Commands,
     inventory_ui_root_query: Query<Entity, With<InventoryUIRoot>>,
     mut cursor_query: Query<(Entity, &mut InventoryCursor)>,
-    mut inventory_query: Query<&mut Inventory>,
+    player_query: Query<(&Inventory, &Equipment), With<Player>>,
     ui_slots_query: Query<Entity, With<UISlots>>,
-    item_query: Query<Option<&Ranged>, With<Item>>,
+    item_query: Query<(Option<&Ranged>, Option<&Equippable>), With<Item>>,
+    key_bindings: Res<KeyBindings>,
 ) {
     let key_press = keyboard_input.clone();
     if key_press.get_just_pressed().len() == 0 {
@@ -90,22 +123,28 @@ pub fn user_input_handler(
         .get_single_mut()
         .expect("while fetching cursor");
 
-    let mut inventory = inventory_query
-        .get_single_mut()
+    let (inventory, equipment) = player_query
+        .get_single()
         .expect("no or more than one inventory");
 
-    let input = get_movement_input(&key_press);
-    if input.received_movement_input() {
-        inventory_cursor.move_cursor(input.y);
-    }
-
+    let input = get_movement_input(&key_press, &key_bindings);
     let mut new_app_state = GameState::RenderInventory;
-    if key_press.just_pressed(KeyCode::E) {
-        new_app_state =
-            schedule_use_item(&mut commands, &inventory_cursor, &mut inventory, item_query);
+    // The inventory is a single column, sideways and diagonal movement keys are free to be hotkeys
+    let selected_item = item_in_row(inventory_cursor.cursor_position, inventory, equipment);
+    if input.x == 0 && input.y != 0 {
+        inventory_cursor.move_cursor(input.y);
+    } else if key_bindings.just_pressed(&key_press, InputAction::UseItem) {
+        new_app_state = schedule_use_item(&mut commands, selected_item, item_query);
+    } else if key_bindings.just_pressed(&key_press, InputAction::DropItem) {
+        new_app_state = schedule_drop_item(&mut commands, selected_item);
+    } else if let Some(slot) = get_hotkey_slot(&key_press, &key_bindings, inventory) {
+        inventory_cursor.cursor_position = slot;
+        new_app_state = schedule_use_item(&mut commands, inventory.get_item(slot), item_query);
     }
 
-    if key_press.just_pressed(KeyCode::I) || key_press.just_pressed(KeyCode::Escape) {
+    if key_bindings.just_pressed(&key_press, InputAction::Inventory)
+        || key_bindings.just_pressed(&key_press, InputAction::Cancel)
+    {
         new_app_state = GameState::AwaitingActionInput;
     }
 
@@ -130,26 +169,107 @@ pub fn user_input_handler(
         .expect("failed to set game state in inventory.use_item");
 }
 
+/// Returns the slot whose hotkey was pressed, if there is an item in it. Keys the inventory
+/// screen already uses for something else (moving, using, dropping, closing) keep their meaning.
+fn get_hotkey_slot(
+    key_press: &Input<KeyCode>,
+    key_bindings: &KeyBindings,
+    inventory: &Inventory,
+) -> Option<usize> {
+    key_bindings
+        .inventory_hotkeys
+        .iter()
+        .take(inventory.inventory_size)
+        .position(|key| key_press.just_pressed(*key))
+        .filter(|slot| inventory.items[*slot].is_some())
+}
+
+/// The item shown in a row of the inventory screen. The inventory slots come first, followed by
+/// one row per equipment slot.
+fn item_in_row(row: usize, inventory: &Inventory, equipment: &Equipment) -> Option<Entity> {
+    match row.checked_sub(inventory.inventory_size) {
+        None => inventory.get_item(row),
+        Some(slot) => EquipmentSlot::ALL
+            .get(slot)
+            .and_then(|slot| equipment.get(*slot)),
+    }
+}
+
+fn get_hotkey_label(slot: usize, key_bindings: &KeyBindings) -> String {
+    match key_bindings.inventory_hotkeys.get(slot) {
+        Some(key) => format!("{}) ", format!("{:?}", key).to_lowercase()),
+        None => String::new(),
+    }
+}
+
+/// Lists what an item does, derived from its components
+fn describe_item(item: ItemDescriptionQueryItem, worn: bool) -> Vec<String> {
+    let (heals, inflicts_damage, ranged, aoe, shape, confusion, equippable, consumable, food) =
+        item;
+    let mut lines = vec![];
+
+    if let Some(heals) = heals {
+        lines.push(format!("Heals {} hp", heals.heal_amount));
+    }
+    if food.is_some() {
+        lines.push("Stills your hunger".to_owned());
+    }
+    if let Some(inflicts_damage) = inflicts_damage {
+        lines.push(format!("Deals {} damage", inflicts_damage.damage));
+    }
+    if let Some(confusion) = confusion {
+        lines.push(format!("Confuses for {} turns", confusion.turns));
+    }
+    if let Some(ranged) = ranged {
+        lines.push(format!("Range: {}", ranged.range));
+    }
+    if let Some(aoe) = aoe {
+        match shape.copied().unwrap_or_default() {
+            AreaShape::Circle => lines.push(format!("Radius: {}", aoe.radius)),
+            shape => lines.push(format!("{} reaching {} tiles", shape.label(), aoe.radius)),
+        }
+    }
+    if let Some(equippable) = equippable {
+        lines.push(format!(
+            "{}: {:+} power, {:+} defense",
+            equippable.slot.label(),
+            equippable.power_bonus,
+            equippable.defense_bonus
+        ));
+    }
+    if worn {
+        lines.push("Equipped".to_owned());
+    }
+    if consumable.is_some() {
+        lines.push("Single use".to_owned());
+    }
+
+    lines
+}
+
 /// System to create an InventoryCursor object and the UI Entities
 pub fn inventory_setup(
     mut commands: Commands,
     mut app_state: ResMut<State<GameState>>,
     player_inventory_query: Query<&Inventory, With<Player>>,
+    ui_font: Res<UIFont>,
 ) {
     let player_inventory = player_inventory_query
         .get_single()
         .expect("while retrieving single player inventory");
+    let row_count = player_inventory.inventory_size + EquipmentSlot::ALL.len();
 
     commands
         .spawn_empty()
-        .insert(InventoryCursor::new(0, player_inventory.inventory_size));
+        .insert(InventoryCursor::new(0, row_count));
 
-    let mut commands_builder = commands.spawn(get_ui_root_bundle());
+    let mut commands_builder = commands.spawn(get_ui_root_bundle(row_count));
     commands_builder.insert(InventoryUIRoot {});
 
     let mut ui_slots = UISlots { slots: vec![] };
     commands_builder.with_children(|parent| {
-        ui_slots = build_ui_slots(parent, player_inventory);
+        ui_slots = build_ui_slots(parent, row_count, &ui_font.0);
+        spawn_item_description(parent, &ui_font.0);
     });
 
     commands.spawn_empty().insert(ui_slots);
@@ -161,12 +281,25 @@ pub fn inventory_setup(
 
 /// System that creates the UI elements when the user enters the inventory.
 /// Changes app state to AwaitingInventoryInput when done.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn inventory_renderer(
     mut app_state: ResMut<State<GameState>>,
-    player_inventory_query: Query<&Inventory, With<Player>>,
+    player_inventory_query: Query<(&Inventory, &Equipment), With<Player>>,
     inventory_cursor_query: Query<&InventoryCursor>,
     ui_slots_query: Query<&UISlots>,
     item_sprite_query: Query<&Sprite, With<Item>>,
+    item_name_query: Query<&ItemName>,
+    item_description_query: Query<ItemDescriptionQueryItem, With<Item>>,
+    slot_count_query: Query<&mut Text, With<InventoryUISlotCount>>,
+    slot_label_query: Query<&mut Text, (With<InventoryUISlotLabel>, Without<InventoryUISlotCount>)>,
+    mut description_query: Query<
+        &mut Text,
+        (
+            With<InventoryUIDescription>,
+            Without<InventoryUISlotCount>,
+            Without<InventoryUISlotLabel>,
+        ),
+    >,
     slot_color_query: Query<(
         &mut BackgroundColor,
         Entity,
@@ -179,8 +312,9 @@ pub fn inventory_renderer(
         With<InventoryUISlotFrame>,
         Without<InventoryUISlot>,
     )>,
+    key_bindings: Res<KeyBindings>,
 ) {
-    let player_inventory = player_inventory_query
+    let (player_inventory, equipment) = player_inventory_query
         .get_single()
         .expect("while retrieving single player inventory");
 
@@ -189,23 +323,51 @@ pub fn inventory_renderer(
         .expect("while retrieving cursor in inventory_renderer");
 
     let ui_slots = ui_slots_query.get_single().unwrap();
-    render_cursor(ui_slots, cursor, cursor_color_query);
+    render_cursor(ui_slots, cursor, player_inventory, cursor_color_query);
 
     render_inventory_slots(
         ui_slots,
         player_inventory,
+        equipment,
         slot_color_query,
+        slot_count_query,
+        slot_label_query,
         item_sprite_query,
+        &item_name_query,
+        &key_bindings,
     );
 
+    let mut description = description_query
+        .get_single_mut()
+        .expect("while retrieving item description in inventory_renderer");
+    // We only care for the first section
+    description.sections[0].value =
+        match item_in_row(cursor.cursor_position, player_inventory, equipment) {
+            Some(item) => {
+                let name = item_name_query
+                    .get(item)
+                    .map(|name| name.name.as_str())
+                    .unwrap_or(UNKNOWN_ITEM_NAME);
+                let mut lines = vec![name.to_owned()];
+                if let Ok(components) = item_description_query.get(item) {
+                    lines.extend(describe_item(components, equipment.is_worn(item)));
+                }
+                lines.join("\n")
+            }
+            None => String::new(),
+        };
+
     app_state
         .set(GameState::AwaitingInventoryInput)
         .expect("failed to set game state in inventory_renderer");
 }
 
+/// Highlights the frame of the selected slot. Frames of the equipment slots are highlighted as
+/// well, so the player can tell them apart from the inventory.
 fn render_cursor(
     ui_slots: &UISlots,
     cursor: &InventoryCursor,
+    player_inventory: &Inventory,
     mut cursor_color_query: Query<(
         &mut BackgroundColor,
         Entity,
@@ -214,41 +376,94 @@ fn render_cursor(
     )>,
 ) {
     let mut cursor_entity = Entity::from_raw(0);
+    let mut equipped_frames = vec![];
     for slot in &ui_slots.slots {
         if slot.inventory_pos == cursor.cursor_position {
-            cursor_entity = slot.cursor_slot.clone();
+            cursor_entity = slot.cursor_slot;
+        }
+
+        if slot.inventory_pos >= player_inventory.inventory_size {
+            equipped_frames.push(slot.cursor_slot);
         }
     }
 
     for (mut color, entity, _, _) in cursor_color_query.iter_mut() {
         if entity == cursor_entity {
-            color.0 = Color::WHITE;
+            color.0 = CURSOR_FRAME_COLOR;
+        } else if equipped_frames.contains(&entity) {
+            color.0 = EQUIPPED_FRAME_COLOR;
         } else {
-            color.0 = Color::GRAY;
+            color.0 = SLOT_FRAME_COLOR;
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn render_inventory_slots(
     ui_slots: &UISlots,
     player_inventory: &Inventory,
+    equipment: &Equipment,
     mut slot_color_query: Query<(
         &mut BackgroundColor,
         Entity,
         With<InventoryUISlot>,
         Without<InventoryUISlotFrame>,
     )>,
+    mut slot_count_query: Query<&mut Text, With<InventoryUISlotCount>>,
+    mut slot_label_query: Query<
+        &mut Text,
+        (With<InventoryUISlotLabel>, Without<InventoryUISlotCount>),
+    >,
     item_sprite_query: Query<&Sprite, With<Item>>,
+    item_name_query: &Query<&ItemName>,
+    key_bindings: &KeyBindings,
 ) {
     let mut entity_map: HashMap<Entity, usize> = HashMap::new();
     for slot in &ui_slots.slots {
         entity_map.insert(slot.item_slot, slot.inventory_pos);
+
+        let item = item_in_row(slot.inventory_pos, player_inventory, equipment);
+        let item_name = item.map(|item| {
+            item_name_query
+                .get(item)
+                .map(|name| name.name.as_str())
+                .unwrap_or(UNKNOWN_ITEM_NAME)
+        });
+        let equipment_slot = slot
+            .inventory_pos
+            .checked_sub(player_inventory.inventory_size)
+            .and_then(|slot| EquipmentSlot::ALL.get(slot));
+
+        if let Ok(mut label_text) = slot_label_query.get_mut(slot.label_text) {
+            label_text.sections[0].value = match (equipment_slot, item_name) {
+                (Some(equipment_slot), Some(name)) => {
+                    format!("{}: {}", equipment_slot.label(), name)
+                }
+                (Some(equipment_slot), None) => equipment_slot.label().to_owned(),
+                (None, Some(name)) => {
+                    format!(
+                        "{}{}",
+                        get_hotkey_label(slot.inventory_pos, key_bindings),
+                        name
+                    )
+                }
+                (None, None) => String::new(),
+            };
+        }
+
+        if let Ok(mut count_text) = slot_count_query.get_mut(slot.count_text) {
+            // Single items don't need a count
+            count_text.sections[0].value = match player_inventory.items.get(slot.inventory_pos) {
+                Some(Some(stack)) if stack.count() > 1 => stack.count().to_string(),
+                _ => String::new(),
+            };
+        }
     }
 
     for (mut color, entity, _, _) in slot_color_query.iter_mut() {
         if let Some(pos) = entity_map.get(&entity) {
-            if let Some(item_in_inventory) = &player_inventory.items[*pos] {
-                match item_sprite_query.get(*item_in_inventory) {
+            if let Some(item_in_inventory) = item_in_row(*pos, player_inventory, equipment) {
+                match item_sprite_query.get(item_in_inventory) {
                     Ok(item_sprite) => color.0 = item_sprite.color,
                     Err(e) => {
                         bevy::log::error!("{}", e);
@@ -276,14 +491,20 @@ fn despawn_inventory(
 pub struct UISlot {
     pub cursor_slot: Entity,
     pub item_slot: Entity,
+    pub count_text: Entity,
+    pub label_text: Entity,
     pub inventory_pos: usize,
 }
 
-fn build_ui_slot(parent: &mut ChildBuilder, y: f32, inventory_pos: usize) -> UISlot {
-    // TODO: Make size of slots depend on size of inventory
-    let slot_height_px = 60.0;
-    let slot_width_px = 60.0;
-    let gap_size_px = 15.0;
+fn build_ui_slot(
+    parent: &mut ChildBuilder,
+    y: f32,
+    inventory_pos: usize,
+    font: &Handle<Font>,
+) -> UISlot {
+    let slot_height_px = SLOT_SIZE_PX;
+    let slot_width_px = SLOT_SIZE_PX;
+    let gap_size_px = SLOT_GAP_PX;
 
     // Spawn cursor frame, surrounding item frame
     let mut cursor_entity_comm = parent.spawn(NodeBundle {
@@ -301,9 +522,11 @@ fn build_ui_slot(parent: &mut ChildBuilder, y: f32, inventory_pos: usize) -> UIS
         ..default()
     });
     cursor_entity_comm.insert(InventoryUISlotFrame {});
+    let cursor_slot = cursor_entity_comm.id();
 
     // Spawn item frame
     let mut item_slot = Entity::from_raw(0);
+    let mut count_text = Entity::from_raw(0);
     cursor_entity_comm.with_children(|par| {
         let mut item_slot_comm = par.spawn(NodeBundle {
             style: Style {
@@ -320,14 +543,68 @@ fn build_ui_slot(parent: &mut ChildBuilder, y: f32, inventory_pos: usize) -> UIS
             ..default()
         });
         item_slot_comm.insert(InventoryUISlot {});
+
+        // Spawn stack size text in the item frame's corner
+        item_slot_comm.with_children(|par| {
+            count_text = par
+                .spawn(TextBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: UiRect {
+                            right: Val::Percent(5.0),
+                            bottom: Val::Percent(0.0),
+                            ..Default::default()
+                        },
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(InventoryUISlotCount {})
+                .id();
+        });
         item_slot = item_slot_comm.id();
     });
 
-    return UISlot {
-        cursor_slot: cursor_entity_comm.id(),
+    // Spawn label right of the cursor frame
+    let label_text = parent
+        .spawn(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(2.0 * slot_width_px + 2.0 * gap_size_px),
+                    bottom: Val::Px(y * slot_height_px + y * gap_size_px + slot_height_px / 3.0),
+                    ..Default::default()
+                },
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            ..default()
+        })
+        .insert(InventoryUISlotLabel {})
+        .id();
+
+    UISlot {
+        cursor_slot,
         item_slot,
+        count_text,
+        label_text,
         inventory_pos,
-    };
+    }
 }
 
 #[derive(Component)]
@@ -335,31 +612,59 @@ pub struct UISlots {
     pub slots: Vec<UISlot>,
 }
 
-fn build_ui_slots(parent: &mut ChildBuilder, inventory: &Inventory) -> UISlots {
-    let mut reverse_y = inventory.inventory_size;
+/// Builds one row per inventory slot followed by one per equipment slot, from the top down
+fn build_ui_slots(parent: &mut ChildBuilder, row_count: usize, font: &Handle<Font>) -> UISlots {
+    let mut reverse_y = row_count;
     let mut slots = vec![];
-    for y in 0..inventory.inventory_size {
+    for y in 0..row_count {
         reverse_y -= 1;
 
-        let ui_slot = build_ui_slot(parent, y as f32, reverse_y);
+        let ui_slot = build_ui_slot(parent, y as f32, reverse_y, font);
         slots.push(ui_slot);
     }
 
     UISlots { slots }
 }
 
-fn get_ui_root_bundle() -> NodeBundle {
+fn spawn_item_description(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    parent
+        .spawn(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(62.0),
+                    top: Val::Percent(5.0),
+                    ..Default::default()
+                },
+                max_size: Size::new(Val::Percent(35.0), Val::Auto),
+                ..default()
+            },
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            ..default()
+        })
+        .insert(InventoryUIDescription {});
+}
+
+/// Frame around the inventory screen, tall enough to fit all rows
+fn get_ui_root_bundle(row_count: usize) -> NodeBundle {
     NodeBundle {
         style: Style {
-            // TODO: Parameterize inventory size
-            // TODO: Use Val::Px
-            size: Size::new(Val::Percent(40.0), Val::Percent(45.0)),
+            size: Size::new(
+                Val::Percent(40.0),
+                Val::Px(row_count as f32 * (SLOT_SIZE_PX + SLOT_GAP_PX)),
+            ),
             position_type: PositionType::Absolute,
             position: UiRect {
                 left: Val::Percent(30.0),
-                right: Val::Percent(30.0),
-                top: Val::Percent(30.0),
-                bottom: Val::Percent(30.0),
+                top: Val::Percent(5.0),
+                ..default()
             },
             ..default()
         },
@@ -368,9 +673,10 @@ fn get_ui_root_bundle() -> NodeBundle {
     }
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn use_item_handler(
     mut commands: Commands,
-    mut inventory_query: Query<&mut Inventory>,
+    mut inventory_query: Query<(Entity, &mut Inventory)>,
     wants_to_use_item_query: Query<(Entity, &WantsToUseItem)>,
     item_query: Query<
         (
@@ -378,17 +684,22 @@ pub fn use_item_handler(
             Option<&Consumable>,
             Option<&InflictsDamage>,
             Option<&Confusion>,
+            Option<&ItemName>,
+            Option<&ProvidesFood>,
+            Option<&AreaOfEffect>,
         ),
         With<Item>,
     >,
+    monster_name_query: Query<&MonsterName>,
     mut player_stats_query: Query<&mut CombatStats, With<Player>>,
+    mut player_hunger_query: Query<&mut HungerClock, With<Player>>,
     mut healthtext_query: Query<&mut Text, (With<HealthText>, Without<ActionLogText>)>,
     mut healthbar_query: Query<&mut Style, With<HealthBar>>,
     mut action_log: ResMut<ActionLog>,
     mut damage_tracker: ResMut<DamageTracker>,
-    game_map: Res<GameMap>,
+    combatant_query: Query<(Entity, &Position), With<CombatStats>>,
 ) {
-    let mut inventory = inventory_query
+    let (user, mut inventory) = inventory_query
         .get_single_mut()
         .expect("Could not get single inventory");
 
@@ -406,31 +717,67 @@ pub fn use_item_handler(
                     use_health_pot(heals, player_stats, healthtext, healthbar);
                 }
 
+                if query.5.is_some() {
+                    if let Ok(mut hunger_clock) = player_hunger_query.get_single_mut() {
+                        *hunger_clock = HungerClock::well_fed();
+                    }
+                    action_log.entries.push(format!(
+                        "You eat the {}",
+                        query
+                            .4
+                            .map(|name| name.name.as_str())
+                            .unwrap_or(UNKNOWN_ITEM_NAME)
+                    ));
+                }
+
                 if let Some(_consumable) = query.1 {
                     inventory.remove_item_by_entity(item.entity);
-                    commands.entity(item.entity).despawn();
+                    commands.entity(item.entity).despawn_recursive();
                 }
 
-                if let Some(targets) = item.targets.clone() {
+                if let Some(targets) = &item.targets {
+                    // Everyone standing in the targeted area. Only a blast can catch the user too.
+                    let victims: Vec<Entity> = combatant_query
+                        .iter()
+                        .filter(|(entity, pos)| {
+                            targets.contains(pos) && (query.6.is_some() || *entity != user)
+                        })
+                        .map(|(entity, _)| entity)
+                        .collect();
+
                     if let Some(inflicts_damage) = query.2 {
-                        for target in &targets {
-                            if let Some(entity) = game_map.tile_content.get(target) {
-                                SufferDamage::add_damage(
-                                    &mut damage_tracker,
-                                    *entity,
-                                    inflicts_damage.damage,
-                                    action_log.as_mut(),
-                                    true,
-                                );
-                            }
+                        let item_name = query
+                            .4
+                            .map(|name| name.name.as_str())
+                            .unwrap_or(UNKNOWN_ITEM_NAME);
+                        for victim in &victims {
+                            let victim_name = if player_stats_query.contains(*victim) {
+                                PLAYER_NAME
+                            } else {
+                                monster_name_query
+                                    .get(*victim)
+                                    .map(|name| name.name.as_str())
+                                    .unwrap_or(UNKNOWN_MONSTER_NAME)
+                            };
+                            SufferDamage::add_damage(
+                                &mut damage_tracker,
+                                *victim,
+                                inflicts_damage.damage,
+                                action_log.as_mut(),
+                                Some(user),
+                                item_name,
+                                victim_name,
+                            );
                         }
                     }
 
                     if let Some(confusion) = query.3 {
-                        for target in &targets {
-                            if let Some(entity) = game_map.tile_content.get(target) {
-                                commands.entity(*entity).insert(confusion.clone());
-                            }
+                        // Only monsters know how to be confused
+                        for victim in victims
+                            .iter()
+                            .filter(|victim| monster_name_query.get(**victim).is_ok())
+                        {
+                            commands.entity(*victim).insert(confusion.clone());
                         }
                     }
                 }
@@ -442,19 +789,159 @@ pub fn use_item_handler(
     }
 }
 
+/// System for processing an equip action by the user. Wearing an item moves it from the
+/// inventory into its equipment slot and adds its bonuses to the player's stats. An item already
+/// worn in the same slot is swapped back into the inventory. Equipping a worn item takes it off.
+pub fn equip_item_handler(
+    mut commands: Commands,
+    wants_to_equip_query: Query<(Entity, &WantsToEquipItem)>,
+    item_query: Query<(&Equippable, Option<&ItemName>), With<Item>>,
+    mut player_query: Query<(&mut CombatStats, &mut Inventory, &mut Equipment), With<Player>>,
+    mut action_log: ResMut<ActionLog>,
+) {
+    for (wants_to_equip_entity, wants_to_equip) in wants_to_equip_query.iter() {
+        commands.entity(wants_to_equip_entity).despawn();
+
+        let item = wants_to_equip.entity;
+        let (equippable, item_name) = match item_query.get(item) {
+            Ok(item) => item,
+            Err(e) => {
+                bevy::log::error!("Can't equip item: {}", e);
+                continue;
+            }
+        };
+        let item_name = item_name
+            .map(|name| name.name.as_str())
+            .unwrap_or(UNKNOWN_ITEM_NAME);
+
+        let (mut player_stats, mut inventory, mut equipment) = player_query
+            .get_single_mut()
+            .expect("Found 0 or more than one player while equipping");
+
+        if equipment.is_worn(item) {
+            match inventory.unequip_item(&mut equipment, item) {
+                Ok(()) => {
+                    equippable.remove_bonus(&mut player_stats);
+                    action_log.entries.push(format!("Unequipped {}", item_name));
+                }
+                Err(InventoryError::InventoryFull) => action_log.entries.push(format!(
+                    "No room in the inventory to take off {}",
+                    item_name
+                )),
+            }
+            continue;
+        }
+
+        let replaced = match inventory.equip_item(&mut equipment, item, equippable.slot) {
+            Ok(replaced) => replaced,
+            Err(InventoryError::InventoryFull) => {
+                action_log.entries.push("Inventory is full!".to_owned());
+                continue;
+            }
+        };
+        if let Some((worn, worn_name)) = replaced.and_then(|worn| item_query.get(worn).ok()) {
+            worn.remove_bonus(&mut player_stats);
+            action_log.entries.push(format!(
+                "Unequipped {}",
+                worn_name
+                    .map(|name| name.name.as_str())
+                    .unwrap_or(UNKNOWN_ITEM_NAME)
+            ));
+        }
+
+        equippable.apply_bonus(&mut player_stats);
+        action_log.entries.push(format!("Equipped {}", item_name));
+    }
+}
+
+/// System for processing a drop action by the user. Takes the item out of the player inventory and
+/// puts it back on the map at the player's position. Worn items are taken off first.
+#[allow(clippy::type_complexity)]
+pub fn drop_item_handler(
+    mut commands: Commands,
+    wants_to_drop_query: Query<(Entity, &WantsToDropItem)>,
+    mut item_query: Query<(&mut Visibility, Option<&ItemName>, Option<&Equippable>), With<Item>>,
+    mut player_query: Query<
+        (&Position, &mut CombatStats, &mut Inventory, &mut Equipment),
+        With<Player>,
+    >,
+    mut action_log: ResMut<ActionLog>,
+    game_config: Res<GameConfig>,
+) {
+    for (wants_to_drop_entity, wants_to_drop) in wants_to_drop_query.iter() {
+        commands.entity(wants_to_drop_entity).despawn();
+
+        let (player_pos, mut player_stats, mut inventory, mut equipment) = player_query
+            .get_single_mut()
+            .expect("Found 0 or more than one player while dropping an item");
+
+        let (mut visibility, item_name, equippable) = match item_query.get_mut(wants_to_drop.entity)
+        {
+            Ok(item) => item,
+            Err(e) => {
+                bevy::log::error!("Can't drop item: {}", e);
+                continue;
+            }
+        };
+
+        if equipment.unequip(wants_to_drop.entity) {
+            if let Some(equippable) = equippable {
+                equippable.remove_bonus(&mut player_stats);
+            }
+        }
+
+        inventory.remove_item_by_entity(wants_to_drop.entity);
+
+        // put item back on the map
+        visibility.is_visible = true;
+        commands.entity(wants_to_drop.entity).insert((
+            player_pos.clone(),
+            item_transform(
+                player_pos,
+                &game_config.tile_properties,
+                &game_config.screen_dimensions,
+            ),
+        ));
+
+        action_log.entries.push(format!(
+            "You drop {}",
+            item_name
+                .map(|name| name.name.as_str())
+                .unwrap_or(UNKNOWN_ITEM_NAME)
+        ));
+    }
+}
+
+fn schedule_drop_item(commands: &mut Commands, selected_item: Option<Entity>) -> GameState {
+    match selected_item {
+        Some(item_entity) => {
+            commands.spawn_empty().insert(WantsToDropItem {
+                entity: item_entity,
+            });
+            GameState::PlayerTurn
+        }
+        None => GameState::RenderInventory,
+    }
+}
+
 fn schedule_use_item(
     commands: &mut Commands,
-    inventory_cursor: &InventoryCursor,
-    inventory: &mut Inventory,
-    item_query: Query<Option<&Ranged>, With<Item>>,
+    selected_item: Option<Entity>,
+    item_query: Query<(Option<&Ranged>, Option<&Equippable>), With<Item>>,
 ) -> GameState {
-    if let Some(item_entity) = &inventory.items[inventory_cursor.cursor_position] {
+    if let Some(item_entity) = &selected_item {
         match item_query.get(*item_entity) {
             Ok(query) => {
-                if let Some(ranged) = query {
+                if query.1.is_some() {
+                    commands.spawn_empty().insert(WantsToEquipItem {
+                        entity: *item_entity,
+                    });
+                    return GameState::PlayerTurn;
+                } else if let Some(ranged) = query.0 {
                     commands.spawn_empty().insert(TargetingModeContext {
                         item: *item_entity,
                         range: ranged.range,
+                        cursor: None,
                     });
                     return GameState::Targeting;
                 } else {
@@ -469,7 +956,7 @@ fn schedule_use_item(
         }
     }
 
-    return GameState::PlayerTurn;
+    GameState::PlayerTurn
 }
 
 fn use_health_pot(
@@ -485,3 +972,52 @@ fn use_health_pot(
     // We only care for the first section
     healthtext.sections[0].value = format!("{}/{}", player_stats.hp, player_stats.max_hp);
 }
+
+#[test]
+fn can_describe_item() {
+    let damage = InflictsDamage { damage: 6 };
+    let ranged = Ranged { range: 6 };
+    let aoe = AreaOfEffect { radius: 4 };
+    let fireball = describe_item(
+        (
+            None,
+            Some(&damage),
+            Some(&ranged),
+            Some(&aoe),
+            None,
+            None,
+            None,
+            Some(&Consumable {}),
+            None,
+        ),
+        false,
+    );
+    assert_eq!(
+        fireball,
+        vec!["Deals 6 damage", "Range: 6", "Radius: 4", "Single use"]
+    );
+
+    let dagger = Equippable {
+        slot: EquipmentSlot::Weapon,
+        power_bonus: 2,
+        defense_bonus: 0,
+    };
+    let worn_dagger = describe_item(
+        (
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&dagger),
+            None,
+            None,
+        ),
+        true,
+    );
+    assert_eq!(
+        worn_dagger,
+        vec!["Weapon: +2 power, +0 defense", "Equipped"]
+    );
+}
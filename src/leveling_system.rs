@@ -0,0 +1,46 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{combat_stats::CombatStats, experience::Experience},
+    damage_system::COLLECT_DEAD_LABEL,
+    user_interface::ActionLog,
+    GameConfig, GameState,
+};
+
+pub struct LevelingSystemPlugin {}
+
+impl Plugin for LevelingSystemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::PlayerTurn)
+                .with_system(level_up)
+                .after(COLLECT_DEAD_LABEL),
+        );
+    }
+}
+
+/// Levels up everything that collected enough xp. Every level gained raises the stats as
+/// configured in the `LevelingSettings` and fully heals.
+fn level_up(
+    mut experience_query: Query<(&mut Experience, &mut CombatStats), Changed<Experience>>,
+    mut action_log: ResMut<ActionLog>,
+    game_config: Res<GameConfig>,
+) {
+    let settings = &game_config.leveling_settings;
+
+    for (mut experience, mut combat_stats) in experience_query.iter_mut() {
+        let levels_gained = experience.level_up(&settings.xp_thresholds);
+        if levels_gained == 0 {
+            continue;
+        }
+
+        combat_stats.max_hp += settings.max_hp_per_level * levels_gained;
+        combat_stats.power += settings.power_per_level * levels_gained;
+        combat_stats.defense += settings.defense_per_level * levels_gained;
+        combat_stats.hp = combat_stats.max_hp;
+
+        action_log
+            .entries
+            .push(format!("Welcome to level {}!", experience.level));
+    }
+}
@@ -1,31 +1,49 @@
+mod area_of_effect;
+mod auto_explore_system;
+mod combat;
 mod components;
 mod configs;
 mod damage_system;
+mod game_over;
+mod hunger_system;
 mod inventory;
+mod leveling_system;
 mod main_menu;
 mod map;
 mod monster;
 mod player;
+mod raws;
+mod save_load;
 mod spawner;
+mod travel_system;
 mod user_interface;
 mod utils;
 mod viewshed;
 
 use std::collections::HashMap;
 
+use auto_explore_system::AutoExplorePlugin;
 use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*, winit::WinitSettings};
 use components::{damage::DamageTracker, user_input::UserInput};
 use configs::game_settings::{
-    GameConfig, GameplaySettings, MapProperties, ScreenDimensions, TileProperties, SCREEN_HEIGHT,
-    SCREEN_WIDTH,
+    parse_seed_arg, GameConfig, GameplaySettings, LevelingSettings, MapProperties,
+    ScreenDimensions, TileProperties, SCREEN_HEIGHT, SCREEN_WIDTH,
 };
+use configs::key_bindings::{KeyBindings, KEY_BINDINGS_PATH};
 use damage_system::DamageSystemPlugin;
+use game_over::GameOverPlugin;
+use hunger_system::HungerSystemPlugin;
 use inventory::plugin::InventorySystemPlugin;
+use leveling_system::LevelingSystemPlugin;
 use main_menu::MainMenuPlugin;
 use map::plugin::GameMapPlugin;
 use monster::MonsterPlugin;
 use player::PlayerPlugin;
+use raws::{Raws, RAWS_PATH};
+use save_load::SaveLoadPlugin;
+use travel_system::TravelPlugin;
 use user_interface::UIPlugin;
+use utils::rng::GameRng;
 use viewshed::ViewshedPlugin;
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
@@ -37,17 +55,22 @@ pub enum GameState {
     PlayerTurn,
     Targeting,
     MonsterTurn,
+    NextLevel,
 
     SetupInventoryScreen,
     RenderInventory,
     AwaitingInventoryInput,
 
     MainMenu,
+    GameOver,
 }
 
 fn main() {
-    App::new()
-        .insert_resource(ClearColor(Color::BLACK))
+    let args: Vec<String> = std::env::args().collect();
+    let seed_arg = parse_seed_arg(&args);
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::BLACK))
         // .insert_resource(WindowDescriptor {
         //     width: SCREEN_WIDTH,
         //     height: SCREEN_HEIGHT,
@@ -59,6 +82,7 @@ fn main() {
                 tile_scale: 1.0,
                 tile_size: 16.0,
                 item_z: 3.0,
+                corpse_z: 2.0,
                 monster_z: 5.0,
                 player_z: 5.0,
             },
@@ -70,18 +94,36 @@ fn main() {
                 map_height: 30,
                 map_width: 60,
                 max_rooms: 10,
+                seed: seed_arg,
             },
             gameplay_settings: GameplaySettings {
                 player_starting_health: 100,
-                health_potion_heal_amount: 20,
-                monster_starting_health: 50,
+            },
+            leveling_settings: LevelingSettings {
+                xp_thresholds: vec![20, 50, 100, 170, 260, 380, 530, 720, 950],
+                max_hp_per_level: 10,
+                power_per_level: 1,
+                defense_per_level: 1,
             },
         })
         .insert_resource(DamageTracker(HashMap::new()))
         .insert_resource(UserInput { x: 0, y: 0 })
         .insert_resource(WinitSettings::desktop_app())
-        .add_plugins(DefaultPlugins)
-        .add_plugin(FrameTimeDiagnosticsPlugin::default())
+        .add_plugins(DefaultPlugins);
+
+    // Logging is set up by the default plugins, anything that may warn or fail goes after them
+    let raws = match Raws::load(RAWS_PATH) {
+        Ok(raws) => raws,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+
+    app.insert_resource(raws)
+        .insert_resource(KeyBindings::load(KEY_BINDINGS_PATH))
+        .insert_resource(GameRng::new(seed_arg.unwrap_or_else(rand::random)))
+        .add_plugin(FrameTimeDiagnosticsPlugin)
         .add_state(GameState::LoadingResources)
         .add_plugin(UIPlugin {})
         .add_plugin(GameMapPlugin {})
@@ -89,7 +131,13 @@ fn main() {
         .add_plugin(ViewshedPlugin {})
         .add_plugin(MonsterPlugin {})
         .add_plugin(DamageSystemPlugin {})
+        .add_plugin(LevelingSystemPlugin {})
+        .add_plugin(HungerSystemPlugin {})
+        .add_plugin(AutoExplorePlugin {})
+        .add_plugin(TravelPlugin {})
         .add_plugin(InventorySystemPlugin {})
         .add_plugin(MainMenuPlugin {})
+        .add_plugin(SaveLoadPlugin {})
+        .add_plugin(GameOverPlugin {})
         .run();
 }
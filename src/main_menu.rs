@@ -1,14 +1,30 @@
 use bevy::{
+    app::AppExit,
     prelude::{
-        App, AssetServer, BuildChildren, Color, Commands, Component, DespawnRecursiveExt, Entity,
-        Input, KeyCode, NodeBundle, Plugin, Query, Res, ResMut, Size, State, SystemSet, TextBundle,
-        With,
+        App, AssetServer, BackgroundColor, BuildChildren, ButtonBundle, Changed, Color, Commands,
+        Component, DespawnRecursiveExt, Entity, EventWriter, Input, KeyCode, NodeBundle, Plugin,
+        Query, Res, ResMut, Size, State, SystemSet, TextBundle, With,
     },
     text::{Text, TextStyle},
-    ui::{PositionType, Style, UiRect, Val},
+    ui::{Interaction, PositionType, Style, UiRect, Val},
 };
 
-use crate::{configs::game_settings::GameConfig, GameState};
+use crate::{
+    components::damage::DamageTracker,
+    configs::{
+        game_settings::GameConfig,
+        key_bindings::{InputAction, KeyBindings},
+    },
+    game_over::GameStats,
+    save_load::{WantsToLoadGame, WantsToSaveGame},
+    spawner::{despawn_game_entities, GameEntityFilter},
+    user_interface::ActionLog,
+    utils::{input_utils::get_movement_input, rng::GameRng},
+    GameState,
+};
+
+const MENU_ENTRY_COLOR: Color = Color::PINK;
+const MENU_ENTRY_SELECTED_COLOR: Color = Color::PURPLE;
 
 pub struct MainMenuPlugin {}
 
@@ -30,12 +46,64 @@ impl Plugin for MainMenuPlugin {
 #[derive(Component)]
 pub struct MainMenuUI {}
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MainMenuEntry {
+    NewGame,
+    Save,
+    Load,
+    Quit,
+}
+
+impl MainMenuEntry {
+    pub const ALL: [MainMenuEntry; 4] = [
+        MainMenuEntry::NewGame,
+        MainMenuEntry::Save,
+        MainMenuEntry::Load,
+        MainMenuEntry::Quit,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            MainMenuEntry::NewGame => "New Game",
+            MainMenuEntry::Save => "Save",
+            MainMenuEntry::Load => "Load",
+            MainMenuEntry::Quit => "Quit",
+        }
+    }
+}
+
+/// Component marking the button of a single menu entry.
+#[derive(Component)]
+pub struct MainMenuButton {
+    pub entry: MainMenuEntry,
+}
+
+/// Component storing the index of the currently selected entry in `MainMenuEntry::ALL`.
+#[derive(Component)]
+pub struct MainMenuCursor {
+    pub selection: usize,
+}
+
+impl MainMenuCursor {
+    /// Moves the cursor up (positive y) or down (negative y), wrapping around at the ends.
+    pub fn move_cursor(&mut self, y: i32) {
+        let entry_count = MainMenuEntry::ALL.len() as i32;
+        let new_selection = (self.selection as i32 - y).rem_euclid(entry_count);
+
+        self.selection = new_selection as usize;
+    }
+
+    pub fn selected_entry(&self) -> MainMenuEntry {
+        MainMenuEntry::ALL[self.selection]
+    }
+}
+
 fn generate_main_menu(
     mut commands: Commands,
     game_config: Res<GameConfig>,
     asset_server: Res<AssetServer>,
 ) {
-    let menu_points = vec!["New Game", "Save", "Load", "Quit"];
+    let menu_entries = MainMenuEntry::ALL;
     let text_font = asset_server.load("fonts/EduVICWANTBeginner-Regular.ttf");
     commands
         .spawn(NodeBundle {
@@ -51,27 +119,29 @@ fn generate_main_menu(
             ..Default::default()
         })
         .insert(MainMenuUI {})
+        .insert(MainMenuCursor { selection: 0 })
         .with_children(|parent| {
-            for (i, point) in menu_points.iter().enumerate() {
+            for (i, entry) in menu_entries.iter().enumerate() {
                 parent
-                    .spawn(NodeBundle {
+                    .spawn(ButtonBundle {
                         style: Style {
                             size: Size::new(Val::Percent(33.3), Val::Percent(10.0)),
                             position_type: PositionType::Absolute,
                             position: UiRect {
-                                bottom: Val::Percent((menu_points.len() - i) as f32 * 20.0),
+                                bottom: Val::Percent((menu_entries.len() - i) as f32 * 20.0),
                                 left: Val::Percent(33.3),
                                 ..Default::default()
                             },
                             ..Default::default()
                         },
-                        background_color: Color::PINK.into(),
+                        background_color: MENU_ENTRY_COLOR.into(),
                         ..Default::default()
                     })
+                    .insert(MainMenuButton { entry: *entry })
                     .with_children(|parent| {
                         parent.spawn(TextBundle {
                             text: Text::from_section(
-                                *point,
+                                entry.label(),
                                 TextStyle {
                                     font: text_font.clone(),
                                     font_size: 27.0,
@@ -85,19 +155,131 @@ fn generate_main_menu(
         });
 }
 
+/// Moves the menu cursor with the movement keys or the mouse and activates the
+/// selected entry on Enter or click.
+#[allow(clippy::too_many_arguments)]
 fn main_menu_system(
     mut app_state: ResMut<State<GameState>>,
     mut commands: Commands,
     mut keyboard_input: ResMut<Input<KeyCode>>,
-    main_menu_ui: Query<Entity, With<MainMenuUI>>,
+    mut main_menu_ui: Query<(Entity, &mut MainMenuCursor), With<MainMenuUI>>,
+    interaction_query: Query<(&Interaction, &MainMenuButton), Changed<Interaction>>,
+    mut button_query: Query<(&MainMenuButton, &mut BackgroundColor)>,
+    game_entities_query: Query<Entity, GameEntityFilter>,
+    mut damage_tracker: ResMut<DamageTracker>,
+    mut action_log: ResMut<ActionLog>,
+    mut game_stats: ResMut<GameStats>,
+    mut game_rng: ResMut<GameRng>,
+    mut app_exit: EventWriter<AppExit>,
+    key_bindings: Res<KeyBindings>,
+    game_config: Res<GameConfig>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
-        let main_menu = main_menu_ui
-            .get_single()
-            .expect("Fetching the main menu UI");
+    let (main_menu, mut cursor) = match main_menu_ui.get_single_mut() {
+        Ok(menu) => menu,
+        // The menu is already gone while a save or load request is being processed
+        Err(_) => return,
+    };
+
+    if key_bindings.just_pressed(&keyboard_input, InputAction::Cancel) {
         commands.entity(main_menu).despawn_recursive();
 
         app_state.pop().expect("Popping main menu game state");
+        keyboard_input.clear();
+        return;
+    }
+
+    let mut activated = key_bindings.just_pressed(&keyboard_input, InputAction::Confirm);
+
+    let movement = get_movement_input(&keyboard_input, &key_bindings);
+    if movement.received_movement_input() {
+        cursor.move_cursor(movement.y);
     }
     keyboard_input.clear();
+
+    for (interaction, button) in interaction_query.iter() {
+        match interaction {
+            Interaction::Clicked | Interaction::Hovered => {
+                cursor.selection = MainMenuEntry::ALL
+                    .iter()
+                    .position(|entry| *entry == button.entry)
+                    .unwrap_or(cursor.selection);
+                activated |= *interaction == Interaction::Clicked;
+            }
+            Interaction::None => {}
+        }
+    }
+
+    for (button, mut color) in button_query.iter_mut() {
+        color.0 = if button.entry == cursor.selected_entry() {
+            MENU_ENTRY_SELECTED_COLOR
+        } else {
+            MENU_ENTRY_COLOR
+        };
+    }
+
+    if !activated {
+        return;
+    }
+
+    match cursor.selected_entry() {
+        MainMenuEntry::NewGame => {
+            commands.entity(main_menu).despawn_recursive();
+            reset_running_game(
+                &mut commands,
+                &game_entities_query,
+                &mut damage_tracker,
+                &mut action_log,
+                &mut game_stats,
+                &mut game_rng,
+                game_config.map_properties.seed,
+            );
+
+            app_state
+                .replace(GameState::LoadingResources)
+                .expect("failed to set game state for a new game");
+        }
+        MainMenuEntry::Save => {
+            // Leaving the main menu is done by the save system once it's done
+            commands.entity(main_menu).despawn_recursive();
+            commands.spawn_empty().insert(WantsToSaveGame {});
+        }
+        MainMenuEntry::Load => {
+            commands.entity(main_menu).despawn_recursive();
+            commands.spawn_empty().insert(WantsToLoadGame {});
+        }
+        MainMenuEntry::Quit => app_exit.send(AppExit),
+    }
+}
+
+/// Throws away the running game so a new one can be generated. The new game uses the seed given
+/// on the command line, or a fresh random one without it.
+pub fn reset_running_game(
+    commands: &mut Commands,
+    game_entities_query: &Query<Entity, GameEntityFilter>,
+    damage_tracker: &mut DamageTracker,
+    action_log: &mut ActionLog,
+    game_stats: &mut GameStats,
+    game_rng: &mut GameRng,
+    seed_arg: Option<u64>,
+) {
+    despawn_game_entities(commands, game_entities_query);
+    *game_rng = GameRng::new(seed_arg.unwrap_or_else(rand::random));
+    damage_tracker.0.clear();
+    action_log.entries = vec!["Adventure awaits!".to_owned()];
+    *game_stats = GameStats::default();
+}
+
+#[test]
+fn can_move_main_menu_cursor() {
+    let mut cursor = MainMenuCursor { selection: 0 };
+
+    cursor.move_cursor(-1);
+    assert_eq!(cursor.selected_entry(), MainMenuEntry::Save);
+
+    cursor.move_cursor(1);
+    cursor.move_cursor(1);
+    assert_eq!(cursor.selected_entry(), MainMenuEntry::Quit);
+
+    cursor.move_cursor(-1);
+    assert_eq!(cursor.selected_entry(), MainMenuEntry::NewGame);
 }
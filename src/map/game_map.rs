@@ -4,7 +4,6 @@ use std::collections::{HashMap, HashSet};
 
 use super::TileType;
 
-
 /// A structure representing the game world as a collection of points.
 /// The upper left corner is at `Position` (0, 0), the lower right corner
 /// is at (width - 1, height - 1).
@@ -13,6 +12,8 @@ pub struct GameMap {
     /// Height in
     pub height: i32,
     pub width: i32,
+    /// How many levels deep into the dungeon this map is, starting at 1
+    pub depth: i32,
     pub tiles: HashMap<Position, TileType>,
     pub visited_tiles: HashSet<Position>,
     pub blocked_tiles: HashSet<Position>,
@@ -23,6 +24,7 @@ impl GameMap {
     pub fn new(
         height: i32,
         width: i32,
+        depth: i32,
         tiles: HashMap<Position, TileType>,
         visited_tiles: HashSet<Position>,
         blocked_tiles: HashSet<Position>,
@@ -31,6 +33,7 @@ impl GameMap {
         GameMap {
             height,
             width,
+            depth,
             tiles,
             visited_tiles,
             blocked_tiles,
@@ -47,13 +50,36 @@ impl GameMap {
             (position.x + 1, position.y),
             (position.x, position.y + 1),
             (position.x, position.y - 1),
+            (position.x - 1, position.y - 1),
+            (position.x - 1, position.y + 1),
+            (position.x + 1, position.y - 1),
+            (position.x + 1, position.y + 1),
         ]
         .into_iter()
         .map(|p| (Position::new(p.0, p.1), 1))
-        .filter(|p| !self.is_blocked(&p.0))
+        .filter(|p| !self.is_blocked(&p.0) && !self.cuts_corner(position, &p.0))
         .collect()
     }
 
+    /// Determines whether a diagonal step squeezes past a wall corner. Diagonal steps are only
+    /// allowed if both orthogonal tiles next to them are free of walls.
+    pub fn cuts_corner(&self, from: &Position, to: &Position) -> bool {
+        if from.x == to.x || from.y == to.y {
+            return false;
+        }
+
+        self.is_wall(&Position::new(to.x, from.y)) || self.is_wall(&Position::new(from.x, to.y))
+    }
+
+    fn is_wall(&self, position: &Position) -> bool {
+        if position.x < 0 || position.x >= self.width || position.y < 0 || position.y >= self.height
+        {
+            return true;
+        }
+
+        self.tiles.get(position) == Some(&TileType::Wall)
+    }
+
     /// Determines whether a given point in the map is occupied (monsters, player, walls)
     pub fn is_blocked(&self, position: &Position) -> bool {
         if position.x < 0 || position.x >= self.width || position.y < 0 || position.y >= self.height
@@ -61,7 +87,7 @@ impl GameMap {
             return true;
         }
 
-        self.blocked_tiles.get(position).is_some()
+        self.blocked_tiles.contains(position)
     }
 
     pub fn set_traversable(&mut self, pos: &Position) {
@@ -84,3 +110,36 @@ impl GameMap {
         self.tile_content.remove(pos);
     }
 }
+
+#[test]
+fn diagonal_steps_do_not_cut_wall_corners() {
+    // A 3x3 room with a wall in the middle of the top row
+    let mut tiles = HashMap::new();
+    for x in 0..3 {
+        for y in 0..3 {
+            tiles.insert(Position::new(x, y), TileType::Floor);
+        }
+    }
+    tiles.insert(Position::new(1, 2), TileType::Wall);
+    let blocked_tiles = HashSet::from([Position::new(1, 2)]);
+    let map = GameMap::new(
+        3,
+        3,
+        1,
+        tiles,
+        HashSet::new(),
+        blocked_tiles,
+        HashMap::new(),
+    );
+
+    let neighbours: Vec<Position> = map
+        .get_traversable_neighbours_with_distance(&Position::new(0, 2))
+        .into_iter()
+        .map(|(neighbour, _)| neighbour)
+        .collect();
+
+    assert_eq!(neighbours, vec![Position::new(0, 1)]);
+    // Would squeeze past the wall at (1, 2)
+    assert!(map.cuts_corner(&Position::new(0, 2), &Position::new(1, 1)));
+    assert!(!map.cuts_corner(&Position::new(0, 1), &Position::new(1, 0)));
+}
@@ -4,47 +4,65 @@ use std::{
 };
 
 use bevy::prelude::*;
-use rand::{prelude::ThreadRng, Rng};
+use rand::{rngs::StdRng, Rng};
 
 use crate::{
-    components::position::Position,
-    configs::game_settings::GameplaySettings,
+    components::{corpse::Corpse, damage::DamageTracker, item::Item, position::Position},
+    monster::Monster,
+    player::Player,
+    raws::Raws,
     spawner::{self, spawn_player},
-    utils::rectangle::Rectangle,
+    user_interface::ActionLog,
+    utils::{rectangle::Rectangle, render::map_pos_to_screen_pos, rng::GameRng},
+    viewshed::Viewshed,
     GameConfig, GameState, MapProperties, ScreenDimensions, TileProperties,
 };
 
-use super::{game_map::GameMap, MainCamera, MaterialHandles, TileType};
+use super::{game_map::GameMap, MainCamera, MaterialHandles, Tile, TileType};
 
-/// Generate the map, load materials and spawn the camera.
-/// Sets the game to `GameState::MapLoaded` when done
-pub fn generate_map(
-    mut commands: Commands,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut app_state: ResMut<State<GameState>>,
-    game_config: Res<GameConfig>,
-) {
+/// Load materials and spawn the camera. Only needs to happen once per app run.
+pub fn setup_map_resources(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
     commands
         .spawn(Camera2dBundle::default())
         .insert(MainCamera {});
 
     let material_handles = MaterialHandles {
-        player: materials.add(Color::rgb_u8(0, 163, 204).into()),
         wall: materials.add(Color::rgb_u8(217, 217, 217).into()),
-        health_potion: materials.add(Color::rgb_u8(34, 139, 34).into()),
         wall_out_of_sight: materials.add(Color::rgb_u8(140, 140, 140).into()),
-        monster: materials.add(Color::rgb_u8(204, 41, 0).into()),
-        friendly: materials.add(Color::rgb_u8(51, 255, 178).into()),
         floor: materials.add(Color::rgb_u8(10, 10, 120).into()),
         floor_out_of_sight: materials.add(Color::rgb_u8(6, 6, 70).into()),
+        down_stairs: materials.add(Color::rgb_u8(255, 215, 0).into()),
+        down_stairs_out_of_sight: materials.add(Color::rgb_u8(140, 118, 0).into()),
     };
-    commands.insert_resource(material_handles.clone());
+    commands.insert_resource(material_handles);
+}
 
-    let map = build_map(
+/// Generate the map and spawn the player, monsters and items.
+/// Runs whenever a new game starts (`GameState::LoadingResources`).
+/// Sets the game to `GameState::MapLoaded` when done
+pub fn generate_map(
+    mut commands: Commands,
+    mut app_state: ResMut<State<GameState>>,
+    game_config: Res<GameConfig>,
+    game_rng: Res<GameRng>,
+    raws: Res<Raws>,
+) {
+    bevy::log::info!("Generating map with seed {}", game_rng.seed);
+    let (map, player_start) = build_map(
         &mut commands,
+        &mut game_rng.level_rng(1),
+        1,
         &game_config.map_properties,
         &game_config.tile_properties,
         &game_config.screen_dimensions,
+        &raws,
+    );
+
+    spawn_player(
+        &mut commands,
+        player_start,
+        &game_config.tile_properties,
+        &game_config.screen_dimensions,
         &game_config.gameplay_settings,
     );
 
@@ -55,14 +73,79 @@ pub fn generate_map(
         .expect("failed to set game state in map.setup()");
 }
 
-/// Generate the world map by randomly generating rooms
+/// Replaces the current level with a freshly generated one, one level deeper.
+/// The player entity, including its stats and inventory, is kept and moved to the start of the
+/// new level. Sets the game to `GameState::MapLoaded` when done
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn descend_to_next_level(
+    mut commands: Commands,
+    mut app_state: ResMut<State<GameState>>,
+    game_config: Res<GameConfig>,
+    game_rng: Res<GameRng>,
+    raws: Res<Raws>,
+    map: Res<GameMap>,
+    level_entities_query: Query<
+        Entity,
+        Or<(
+            With<Tile>,
+            With<Monster>,
+            With<Corpse>,
+            (With<Item>, With<Position>),
+        )>,
+    >,
+    mut player_query: Query<(Entity, &mut Position, &mut Transform, &mut Viewshed), With<Player>>,
+    mut action_log: ResMut<ActionLog>,
+    mut damage_tracker: ResMut<DamageTracker>,
+) {
+    for entity in level_entities_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let (next_map, player_start) = build_map(
+        &mut commands,
+        &mut game_rng.level_rng(map.depth + 1),
+        map.depth + 1,
+        &game_config.map_properties,
+        &game_config.tile_properties,
+        &game_config.screen_dimensions,
+        &raws,
+    );
+
+    let (player, mut player_pos, mut player_tf, mut viewshed) = player_query
+        .get_single_mut()
+        .expect("Found 0 or more than one player while descending");
+    // Damage dealt to anything left behind on the old level would point at despawned entities
+    damage_tracker.0.retain(|entity, _| *entity == player);
+    player_tf.translation = map_pos_to_screen_pos(
+        &player_start,
+        game_config.tile_properties.player_z,
+        game_config.tile_properties.tile_size,
+        &game_config.screen_dimensions,
+    );
+    *player_pos = player_start;
+    viewshed.dirty = true;
+
+    action_log
+        .entries
+        .push(format!("You descend to depth {}", next_map.depth));
+    commands.insert_resource(next_map);
+
+    app_state
+        .overwrite_set(GameState::MapLoaded)
+        .expect("failed to set game state after descending");
+}
+
+/// Generate the world map by randomly generating rooms.
+/// Returns the map and the position the player starts at
 fn build_map(
     commands: &mut Commands,
+    rng: &mut StdRng,
+    depth: i32,
     map_properties: &MapProperties,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-    gameplay_settings: &GameplaySettings,
-) -> GameMap {
+    raws: &Raws,
+) -> (GameMap, Position) {
     let mut tiles: HashMap<Position, TileType> = HashMap::new();
     let mut collidables: HashSet<Position> = HashSet::new();
 
@@ -77,42 +160,44 @@ fn build_map(
     let mut game_map = GameMap::new(
         map_properties.map_height,
         map_properties.map_width,
+        depth,
         tiles,
         HashSet::new(),
         collidables,
         HashMap::new(),
     );
 
-    generate_rooms(
+    let player_start = generate_rooms(
         commands,
+        rng,
         &mut game_map,
         tile_properties,
         screen_dimensions,
-        gameplay_settings,
+        raws,
         map_properties.max_rooms,
     );
 
-    game_map
+    (game_map, player_start)
 }
 
-/// Creates non-overlapping rooms on the map and fills them with the player (first room) or
-/// monsters (all other rooms)
+/// Creates non-overlapping rooms on the map and fills them with monsters and items. The first
+/// room is kept free for the player, whose start position is returned. The way down to the next
+/// level is placed in the last room.
 fn generate_rooms(
     commands: &mut Commands,
+    rng: &mut StdRng,
     game_map: &mut GameMap,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-    gameplay_settings: &GameplaySettings,
+    raws: &Raws,
     max_rooms: u32,
-) {
+) -> Position {
     let room_min_height = game_map.height / 10;
     let room_min_width = game_map.width / 10;
     let room_max_height = game_map.height / 5;
     let room_max_width = game_map.width / 5;
     let mut rooms: Vec<Rectangle> = vec![];
 
-    let mut rand = rand::thread_rng();
-
     for room_no in 0..max_rooms {
         let new_room = generate_room(
             room_min_height,
@@ -121,7 +206,7 @@ fn generate_rooms(
             room_max_width,
             game_map.width,
             game_map.height,
-            &mut rand,
+            rng,
         );
 
         for room in rooms.iter() {
@@ -130,24 +215,16 @@ fn generate_rooms(
             }
         }
 
-        if room_no == 0 {
-            // Place player in first room
-            let (x, y) = &new_room.get_center();
-            spawn_player(
-                commands,
-                Position { x: *x, y: *y },
-                tile_properties,
-                screen_dimensions,
-                gameplay_settings,
-            );
-        } else {
+        // The first room is reserved for the player
+        if room_no != 0 {
             spawner::spawn_room(
                 commands,
                 &new_room,
-                &mut rand,
+                game_map.depth,
+                rng,
+                raws,
                 tile_properties,
                 screen_dimensions,
-                gameplay_settings,
             );
         }
 
@@ -164,7 +241,7 @@ fn generate_rooms(
             // Mix tunnel generation up a little
             let tunnel_horizontal: Rectangle;
             let tunnel_vertical: Rectangle;
-            if rand.gen_range(1..=2) == 1 {
+            if rng.gen_range(1..=2) == 1 {
                 tunnel_horizontal = generate_horizontal_tunnel(prev_x, curr_x, prev_y);
                 tunnel_vertical = generate_vertical_tunnel(prev_y, curr_y, curr_x);
             } else {
@@ -176,6 +253,16 @@ fn generate_rooms(
         }
         prev_room = Some(room);
     }
+
+    if let Some(last_room) = rooms.last() {
+        let (x, y) = last_room.get_center();
+        game_map
+            .tiles
+            .insert(Position { x, y }, TileType::DownStairs);
+    }
+
+    let (x, y) = rooms[0].get_center();
+    Position { x, y }
 }
 
 fn generate_room(
@@ -185,12 +272,12 @@ fn generate_room(
     max_width: i32,
     map_width: i32,
     map_height: i32,
-    rand: &mut ThreadRng,
+    rng: &mut StdRng,
 ) -> Rectangle {
-    let height = rand.gen_range(min_height..=max_height);
-    let width = rand.gen_range(min_width..=max_width);
-    let x = rand.gen_range(1..(map_width - width));
-    let y = rand.gen_range(1..(map_height - height));
+    let height = rng.gen_range(min_height..=max_height);
+    let width = rng.gen_range(min_width..=max_width);
+    let x = rng.gen_range(1..(map_width - width));
+    let y = rng.gen_range(1..(map_height - height));
 
     Rectangle::new(x, y, width, height)
 }
@@ -227,3 +314,54 @@ fn apply_room_to_map(map: &mut GameMap, room: &Rectangle) {
         }
     }
 }
+
+#[test]
+fn same_seed_and_depth_generate_same_level() {
+    use bevy::ecs::system::CommandQueue;
+
+    use crate::{components::item::ItemName, monster::MonsterName};
+
+    let raws = Raws::from_ron(include_str!("../../assets/raws/spawns.ron")).unwrap();
+    let map_properties = MapProperties {
+        map_height: 30,
+        map_width: 60,
+        max_rooms: 10,
+        seed: None,
+    };
+
+    let generate = |seed: u64, depth: i32| {
+        let mut world = World::new();
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &world);
+        let (map, player_start) = build_map(
+            &mut commands,
+            &mut GameRng::new(seed).level_rng(depth),
+            depth,
+            &map_properties,
+            &TileProperties::default(),
+            &ScreenDimensions::default(),
+            &raws,
+        );
+        queue.apply(&mut world);
+
+        let mut spawns: Vec<(Position, String)> = world
+            .query::<(&Position, Option<&MonsterName>, Option<&ItemName>)>()
+            .iter(&world)
+            .map(|(pos, monster, item)| {
+                let name = monster
+                    .map(|monster| monster.name.clone())
+                    .or_else(|| item.map(|item| item.name.clone()))
+                    .unwrap_or_default();
+                (pos.clone(), name)
+            })
+            .collect();
+        spawns.sort_by_key(|(pos, name)| (pos.x, pos.y, name.clone()));
+
+        (map.tiles, player_start, spawns)
+    };
+
+    let level = generate(42, 3);
+    assert!(!level.2.is_empty());
+    assert_eq!(level, generate(42, 3));
+    assert_ne!(level.0, generate(42, 4).0);
+}
@@ -2,6 +2,7 @@ use bevy::{
     prelude::{Component, Handle, Resource},
     sprite::ColorMaterial,
 };
+use serde::{Deserialize, Serialize};
 
 pub mod game_map;
 mod generate_map_system;
@@ -9,22 +10,31 @@ pub mod plugin;
 mod render_map_system;
 mod spawn_map_tiles_system;
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
+    DownStairs,
+}
+
+impl TileType {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TileType::Wall => "Wall",
+            TileType::Floor => "Floor",
+            TileType::DownStairs => "Stairs down",
+        }
+    }
 }
 
 #[derive(Clone, Resource, Debug, Default)]
 pub struct MaterialHandles {
-    pub player: Handle<ColorMaterial>,
     pub wall: Handle<ColorMaterial>,
     pub wall_out_of_sight: Handle<ColorMaterial>,
-    pub health_potion: Handle<ColorMaterial>,
-    pub monster: Handle<ColorMaterial>,
-    pub friendly: Handle<ColorMaterial>,
     pub floor: Handle<ColorMaterial>,
     pub floor_out_of_sight: Handle<ColorMaterial>,
+    pub down_stairs: Handle<ColorMaterial>,
+    pub down_stairs_out_of_sight: Handle<ColorMaterial>,
 }
 
 #[derive(Component)]
@@ -2,20 +2,30 @@ use bevy::prelude::{App, Plugin, SystemSet};
 
 use crate::GameState;
 
-use super::generate_map_system::generate_map;
-use super::render_map_system::render_map;
+use super::game_map::GameMap;
+use super::generate_map_system::{descend_to_next_level, generate_map, setup_map_resources};
+use super::render_map_system::{render_map, spawn_glyph_text};
 use super::spawn_map_tiles_system::spawn_map_tiles;
 
 pub struct GameMapPlugin {}
 
 impl Plugin for GameMapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(generate_map)
+        // Replaced by the generated map once a game starts
+        app.init_resource::<GameMap>()
+            .add_startup_system(setup_map_resources)
+            .add_system_set(
+                SystemSet::on_enter(GameState::LoadingResources).with_system(generate_map),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::NextLevel).with_system(descend_to_next_level),
+            )
             .add_system_set(SystemSet::on_enter(GameState::MapLoaded).with_system(spawn_map_tiles))
             .add_system_set(
                 SystemSet::on_update(GameState::Render)
                     .with_system(render_map)
                     .label("render_map"),
-            );
+            )
+            .add_system(spawn_glyph_text);
     }
 }
@@ -2,7 +2,13 @@ use std::collections::HashSet;
 
 use bevy::prelude::*;
 
-use crate::{components::position::Position, player::Player, viewshed::Viewshed, GameState};
+use crate::{
+    components::{glyph::Glyph, position::Position},
+    player::Player,
+    user_interface::UIFont,
+    viewshed::Viewshed,
+    GameConfig, GameState,
+};
 
 use super::{game_map::GameMap, MaterialHandles, Tile, TileType};
 
@@ -64,6 +70,7 @@ fn render_tiles(
             match *tile_type {
                 TileType::Floor => material_handler = materials.floor.clone(),
                 TileType::Wall => material_handler = materials.wall.clone(),
+                TileType::DownStairs => material_handler = materials.down_stairs.clone(),
             }
         } else if map.visited_tiles.contains(entity_pos) {
             // Render the visited, currently out of sight parts of the map (tiles) in a different color
@@ -71,6 +78,9 @@ fn render_tiles(
             match *tile_type {
                 TileType::Floor => material_handler = materials.floor_out_of_sight.clone(),
                 TileType::Wall => material_handler = materials.wall_out_of_sight.clone(),
+                TileType::DownStairs => {
+                    material_handler = materials.down_stairs_out_of_sight.clone()
+                }
             }
         } else {
             visible_entity.is_visible = false;
@@ -84,3 +94,30 @@ fn render_tiles(
         sprite.color = color;
     }
 }
+
+/// Draws the glyph of newly spawned monsters and items on top of their sprite
+pub fn spawn_glyph_text(
+    mut commands: Commands,
+    glyph_query: Query<(Entity, &Glyph), Added<Glyph>>,
+    font: Res<UIFont>,
+    game_config: Res<GameConfig>,
+) {
+    for (entity, glyph) in glyph_query.iter() {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    glyph.glyph.to_string(),
+                    TextStyle {
+                        font: font.0.clone(),
+                        font_size: game_config.tile_properties.tile_size,
+                        color: Color::BLACK,
+                    },
+                )
+                .with_alignment(TextAlignment::CENTER),
+                // Slightly in front of the sprite
+                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                ..default()
+            });
+        });
+    }
+}
@@ -15,20 +15,19 @@ pub fn spawn_map_tiles(
     game_config: Res<GameConfig>,
 ) {
     for (pos, tile) in map.tiles.iter() {
-        let material: ColorMaterial;
-        match tile {
-            TileType::Floor => {
-                material = material_assets
-                    .get(&materials.floor)
-                    .expect("missing floor material in ColorMaterial assets")
-                    .clone()
-            }
-            TileType::Wall => {
-                material = material_assets
-                    .get(&materials.wall)
-                    .expect("missing wall material in ColorMaterial assets")
-                    .clone()
-            }
+        let material: ColorMaterial = match tile {
+            TileType::Floor => material_assets
+                .get(&materials.floor)
+                .expect("missing floor material in ColorMaterial assets")
+                .clone(),
+            TileType::Wall => material_assets
+                .get(&materials.wall)
+                .expect("missing wall material in ColorMaterial assets")
+                .clone(),
+            TileType::DownStairs => material_assets
+                .get(&materials.down_stairs)
+                .expect("missing stairs material in ColorMaterial assets")
+                .clone(),
         };
 
         let mut entity = commands.spawn_empty();
@@ -1,21 +1,23 @@
 use bevy::prelude::*;
+use rand::rngs::StdRng;
+use serde::Deserialize;
 
 use crate::{
+    combat::melee_attack,
     components::position::Position,
-    components::{
-        combat_stats::CombatStats, damage::DamageTracker, damage::SufferDamage, item::Confusion,
-    },
+    components::{combat_stats::CombatStats, damage::DamageTracker, item::Confusion},
     configs::game_settings::TileProperties,
+    game_over::GameStats,
     map::game_map::GameMap,
-    player::Player,
+    player::{Player, PLAYER_NAME},
     user_interface::ActionLog,
-    utils::render::map_pos_to_screen_pos,
+    utils::{render::map_pos_to_screen_pos, rng::GameRng},
     viewshed::Viewshed,
     GameConfig, GameState, ScreenDimensions,
 };
 
-pub const MONSTER_FOV: i32 = 8;
 pub const MONSTER_TURN_LABEL: &str = "monster_turn";
+pub const UNKNOWN_MONSTER_NAME: &str = "Something";
 
 pub struct MonsterPlugin {}
 
@@ -31,10 +33,28 @@ impl Plugin for MonsterPlugin {
 #[derive(Component)]
 pub struct Monster {}
 
+#[derive(Component, Debug, Clone)]
+pub struct MonsterName {
+    pub name: String,
+}
+
+/// How a monster acts once it has spotted the player
+#[derive(Component, Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum MonsterBehaviour {
+    /// Chases the player and attacks in melee
+    Aggressive,
+    /// Like `Aggressive`, but runs away once it's below half of its health
+    Cowardly,
+    /// Never moves, but attacks the player when adjacent
+    Stationary,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn monster_ai(
     mut commands: Commands,
     mut map: ResMut<GameMap>,
     mut damage_tracker: ResMut<DamageTracker>,
+    mut game_rng: ResMut<GameRng>,
     mut app_state: ResMut<State<GameState>>,
     mut monsters_and_player_set: ParamSet<(
         Query<
@@ -44,13 +64,16 @@ fn monster_ai(
                 &mut Position,
                 &mut Viewshed,
                 &CombatStats,
+                &MonsterName,
+                &MonsterBehaviour,
                 Option<&mut Confusion>,
             ),
             With<Monster>,
         >,
-        Query<(Entity, &Position), With<Player>>,
+        Query<(Entity, &Position, &CombatStats), With<Player>>,
     )>,
     mut action_log: ResMut<ActionLog>,
+    mut game_stats: ResMut<GameStats>,
     game_config: Res<GameConfig>,
 ) {
     let q = monsters_and_player_set.p1();
@@ -60,6 +83,7 @@ fn monster_ai(
 
     let player_pos = player_tuple.1.to_owned();
     let player_entity = player_tuple.0.to_owned();
+    let player_stats = player_tuple.2.clone();
 
     let action_log_ref = action_log.as_mut();
 
@@ -69,6 +93,8 @@ fn monster_ai(
         mut monster_pos,
         mut viewshed,
         combat_stats,
+        monster_name,
+        behaviour,
         confusion_option,
     ) in monsters_and_player_set.p0().iter_mut()
     {
@@ -90,9 +116,41 @@ fn monster_ai(
             }
         }
 
-        if sees_player {
+        if !sees_player {
+            continue;
+        }
+
+        let flees =
+            *behaviour == MonsterBehaviour::Cowardly && combat_stats.hp * 2 < combat_stats.max_hp;
+
+        if flees {
+            flee_from_player(
+                monster_entity,
+                &mut monster_tf,
+                &mut monster_pos,
+                &player_pos,
+                &mut map,
+                &mut viewshed,
+                &game_config.tile_properties,
+                &game_config.screen_dimensions,
+            );
+        } else if *behaviour == MonsterBehaviour::Stationary {
+            if can_reach_in_melee(&map, &monster_pos, &player_pos) {
+                attack_player(
+                    monster_entity,
+                    &monster_name.name,
+                    combat_stats,
+                    &mut damage_tracker,
+                    &mut game_rng.rng,
+                    player_entity,
+                    &player_stats,
+                    action_log_ref,
+                );
+            }
+        } else {
             move_to_player(
                 monster_entity,
+                &monster_name.name,
                 &mut monster_tf,
                 &mut monster_pos,
                 &player_pos,
@@ -100,7 +158,9 @@ fn monster_ai(
                 &mut map,
                 &mut viewshed,
                 &mut damage_tracker,
+                &mut game_rng.rng,
                 player_entity,
+                &player_stats,
                 action_log_ref,
                 &game_config.tile_properties,
                 &game_config.screen_dimensions,
@@ -108,13 +168,25 @@ fn monster_ai(
         }
     }
 
+    // Every round ends with the monsters' turn
+    game_stats.turns += 1;
+
     app_state
         .set(GameState::Render)
         .expect("failed to set game state in monster_ai");
 }
 
+/// Whether a monster can hit the player from where it stands. Like moves, attacks can't reach
+/// diagonally past a wall corner.
+fn can_reach_in_melee(map: &GameMap, monster_pos: &Position, player_pos: &Position) -> bool {
+    monster_pos.is_adjacent_to(player_pos) && !map.cuts_corner(monster_pos, player_pos)
+}
+
+/// Walks one step towards the player along the shortest path or attacks the player if adjacent
+#[allow(clippy::too_many_arguments)]
 fn move_to_player(
     monster_entity: Entity,
+    monster_name: &str,
     monster_tf: &mut Transform,
     monster_pos: &mut Position,
     player_pos: &Position,
@@ -122,7 +194,9 @@ fn move_to_player(
     map: &mut GameMap,
     viewshed: &mut Viewshed,
     damage_tracker: &mut ResMut<DamageTracker>,
+    rng: &mut StdRng,
     player_entity: Entity,
+    player_stats: &CombatStats,
     action_log: &mut ActionLog,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
@@ -131,41 +205,168 @@ fn move_to_player(
     let path_result_opt = pathfinding::directed::astar::astar(
         &position,
         |position| map.get_traversable_neighbours_with_distance(position),
-        |pos| pos.get_airline_distance(player_pos),
-        |pos| pos.is_adjacent_to(player_pos),
+        |pos| pos.get_step_distance(player_pos),
+        |pos| can_reach_in_melee(map, pos, player_pos),
     );
 
     if let Some(path_result) = path_result_opt {
         if path_result.0.len() > 1 {
-            // unblock old position
-            map.remove_blocked(monster_pos);
-            map.remove_tile_content(monster_pos);
-
-            monster_pos.x = path_result.0[1].x;
-            monster_pos.y = path_result.0[1].y;
-
-            // block new position
-            map.set_blocked(monster_pos.clone());
-            map.set_tile_content(monster_pos.clone(), monster_entity);
-
-            monster_tf.translation = map_pos_to_screen_pos(
+            move_monster(
+                monster_entity,
+                monster_tf,
                 monster_pos,
-                tile_properties.monster_z,
-                tile_properties.tile_size,
+                &path_result.0[1],
+                map,
+                viewshed,
+                tile_properties,
                 screen_dimensions,
             );
-
-            viewshed.dirty = true; // Monster moved, re-compute viewshed
         } else {
-            // attack the player in melee
-            SufferDamage::add_damage(
+            attack_player(
+                monster_entity,
+                monster_name,
+                monster_combat_stats,
                 damage_tracker,
+                rng,
                 player_entity,
-                monster_combat_stats.power,
+                player_stats,
                 action_log,
-                false,
             );
-            bevy::log::info!("Player has been hit with {}", monster_combat_stats.power,);
         }
     }
 }
+
+/// Steps onto the neighbouring tile that is farthest away from the player, if there is one that
+/// is farther away than the current position
+#[allow(clippy::too_many_arguments)]
+fn flee_from_player(
+    monster_entity: Entity,
+    monster_tf: &mut Transform,
+    monster_pos: &mut Position,
+    player_pos: &Position,
+    map: &mut GameMap,
+    viewshed: &mut Viewshed,
+    tile_properties: &TileProperties,
+    screen_dimensions: &ScreenDimensions,
+) {
+    let current_distance = monster_pos.get_airline_distance(player_pos);
+    let escape_route = map
+        .get_traversable_neighbours_with_distance(monster_pos)
+        .into_iter()
+        .map(|(pos, _)| pos)
+        .max_by_key(|pos| pos.get_airline_distance(player_pos));
+
+    if let Some(new_pos) = escape_route {
+        if new_pos.get_airline_distance(player_pos) > current_distance {
+            move_monster(
+                monster_entity,
+                monster_tf,
+                monster_pos,
+                &new_pos,
+                map,
+                viewshed,
+                tile_properties,
+                screen_dimensions,
+            );
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn move_monster(
+    monster_entity: Entity,
+    monster_tf: &mut Transform,
+    monster_pos: &mut Position,
+    new_pos: &Position,
+    map: &mut GameMap,
+    viewshed: &mut Viewshed,
+    tile_properties: &TileProperties,
+    screen_dimensions: &ScreenDimensions,
+) {
+    // unblock old position
+    map.remove_blocked(monster_pos);
+    map.remove_tile_content(monster_pos);
+
+    monster_pos.x = new_pos.x;
+    monster_pos.y = new_pos.y;
+
+    // block new position
+    map.set_blocked(monster_pos.clone());
+    map.set_tile_content(monster_pos.clone(), monster_entity);
+
+    monster_tf.translation = map_pos_to_screen_pos(
+        monster_pos,
+        tile_properties.monster_z,
+        tile_properties.tile_size,
+        screen_dimensions,
+    );
+
+    viewshed.dirty = true; // Monster moved, re-compute viewshed
+}
+
+/// Attack the player in melee
+#[allow(clippy::too_many_arguments)]
+fn attack_player(
+    monster_entity: Entity,
+    monster_name: &str,
+    monster_combat_stats: &CombatStats,
+    damage_tracker: &mut ResMut<DamageTracker>,
+    rng: &mut StdRng,
+    player_entity: Entity,
+    player_stats: &CombatStats,
+    action_log: &mut ActionLog,
+) {
+    let outcome = melee_attack(
+        damage_tracker,
+        action_log,
+        rng,
+        monster_entity,
+        monster_name,
+        monster_combat_stats,
+        player_entity,
+        PLAYER_NAME,
+        player_stats,
+    );
+    bevy::log::info!("{} attacked the player: {:?}", monster_name, outcome);
+}
+
+#[test]
+fn monsters_do_not_attack_past_wall_corners() {
+    use crate::map::TileType;
+    use std::collections::{HashMap, HashSet};
+
+    // A 2x2 map with a wall in the upper left, the player and the monster on the diagonal
+    // next to it
+    let mut tiles = HashMap::from([
+        (Position::new(0, 0), TileType::Floor),
+        (Position::new(1, 0), TileType::Floor),
+        (Position::new(0, 1), TileType::Wall),
+        (Position::new(1, 1), TileType::Floor),
+    ]);
+    let map = GameMap::new(
+        2,
+        2,
+        1,
+        tiles.clone(),
+        HashSet::new(),
+        HashSet::new(),
+        HashMap::new(),
+    );
+    let monster_pos = Position::new(0, 0);
+    let player_pos = Position::new(1, 1);
+
+    assert!(!can_reach_in_melee(&map, &monster_pos, &player_pos));
+    assert!(can_reach_in_melee(&map, &Position::new(1, 0), &player_pos));
+
+    tiles.insert(Position::new(0, 1), TileType::Floor);
+    let open_map = GameMap::new(
+        2,
+        2,
+        1,
+        tiles,
+        HashSet::new(),
+        HashSet::new(),
+        HashMap::new(),
+    );
+    assert!(can_reach_in_melee(&open_map, &monster_pos, &player_pos));
+}
@@ -1,23 +1,30 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, window::RequestRedraw};
 
 use crate::{
+    auto_explore_system::AutoExploring,
+    combat::melee_attack,
     components::position::Position,
     components::{
         combat_stats::CombatStats,
         damage::DamageTracker,
-        damage::SufferDamage,
+        hunger::{HungerClock, HungerState},
         item::{Item, ItemName, UNKNOWN_ITEM_NAME},
         user_input::UserInput,
     },
+    configs::key_bindings::{InputAction, KeyBindings},
     inventory::components::WantsToPickupItem,
-    map::game_map::GameMap,
+    map::{game_map::GameMap, TileType},
+    monster::{Monster, MonsterName, UNKNOWN_MONSTER_NAME},
     user_interface::ActionLog,
-    utils::{input_utils::get_movement_input, render::map_pos_to_screen_pos},
-    viewshed::Viewshed,
+    utils::{input_utils::get_movement_input, render::map_pos_to_screen_pos, rng::GameRng},
+    viewshed::{Viewshed, PLAYER_VIEWSHED_LABEL},
     GameConfig, GameState,
 };
 
 pub const PLAYER_FOV: i32 = 10;
+pub const PLAYER_NAME: &str = "Player";
+/// Hp regained per turn spent resting out of sight of any monster
+const REST_HEAL_AMOUNT: i32 = 1;
 
 pub const PLAYER_TURN_LABEL: &str = "player_turn";
 pub struct PlayerPlugin {}
@@ -26,9 +33,11 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(
             SystemSet::on_update(GameState::PlayerTurn)
-                .with_system(player_turn.label(PLAYER_TURN_LABEL)),
+                .with_system(player_turn.label(PLAYER_TURN_LABEL))
+                .with_system(rest_handler),
         );
         app.add_system(player_input.label("await_input"));
+        app.add_system(rest.after(PLAYER_VIEWSHED_LABEL).before("await_input"));
     }
 }
 
@@ -38,34 +47,61 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct Player {}
 
+/// Component that flags the player skipping a turn to rest.
+#[derive(Component)]
+pub struct WantsToRest {}
+
+/// Component that flags the player resting turn after turn until interrupted, one turn per
+/// frame.
+#[derive(Component)]
+pub struct Resting {}
+
+#[allow(clippy::too_many_arguments)]
 fn player_input(
     mut keyboard_input: ResMut<Input<KeyCode>>,
     mut user_input_res: ResMut<UserInput>,
     mut app_state: ResMut<State<GameState>>,
     items_query: Query<(Entity, &Position, Option<&ItemName>), With<Item>>,
-    player_query: Query<&Position, With<Player>>,
+    player_query: Query<(Entity, &Position), With<Player>>,
     mut commands: Commands,
+    map: Res<GameMap>,
+    mut action_log: ResMut<ActionLog>,
+    key_bindings: Res<KeyBindings>,
+    mut redraw: EventWriter<RequestRedraw>,
 ) {
     if *app_state.current() != GameState::AwaitingActionInput {
         return;
     }
 
-    let user_input = get_movement_input(&keyboard_input);
+    let user_input = get_movement_input(&keyboard_input, &key_bindings);
     let mut received_input = user_input.received_movement_input();
 
-    if keyboard_input.just_pressed(KeyCode::G) {
-        let player_pos = player_query
+    if received_input {
+        let (_, player_pos) = player_query
+            .get_single()
+            .expect("Player does not exist or has no position");
+        let new_pos = Position::new(player_pos.x + user_input.x, player_pos.y + user_input.y);
+
+        // Refused moves don't cost a turn
+        if map.cuts_corner(player_pos, &new_pos) {
+            action_log
+                .entries
+                .push("You can't squeeze past the corner.".to_owned());
+            received_input = false;
+        }
+    }
+
+    if key_bindings.just_pressed(&keyboard_input, InputAction::PickUp) {
+        let (_, player_pos) = player_query
             .get_single()
             .expect("Player does not exist or has no position");
 
         for (entity, item_pos, item_name) in items_query.iter() {
             if player_pos == item_pos {
-                let name;
-                if item_name.is_none() {
-                    name = UNKNOWN_ITEM_NAME.to_owned();
-                } else {
-                    name = item_name.unwrap().name.clone()
-                }
+                let name = match item_name {
+                    Some(item_name) => item_name.name.clone(),
+                    None => UNKNOWN_ITEM_NAME.to_owned(),
+                };
 
                 commands.spawn_empty().insert(WantsToPickupItem {
                     entity,
@@ -77,11 +113,49 @@ fn player_input(
         }
     }
 
-    if keyboard_input.just_pressed(KeyCode::I) {
+    if key_bindings.just_pressed(&keyboard_input, InputAction::Wait) {
+        commands.spawn_empty().insert(WantsToRest {});
+        received_input = true;
+    }
+
+    if key_bindings.just_pressed(&keyboard_input, InputAction::Rest) {
+        if let Ok((player, _)) = player_query.get_single() {
+            commands.entity(player).insert(Resting {});
+            redraw.send(RequestRedraw);
+        }
+    }
+
+    if key_bindings.just_pressed(&keyboard_input, InputAction::AutoExplore) {
+        if let Ok((player, _)) = player_query.get_single() {
+            commands.entity(player).insert(AutoExploring::default());
+            redraw.send(RequestRedraw);
+        }
+    }
+
+    let mut descend = false;
+    if key_bindings.just_pressed(&keyboard_input, InputAction::Descend) {
+        let (_, player_pos) = player_query
+            .get_single()
+            .expect("Player does not exist or has no position");
+
+        if map.tiles.get(player_pos) == Some(&TileType::DownStairs) {
+            descend = true;
+        } else {
+            action_log
+                .entries
+                .push("There is no way down from here.".to_owned());
+        }
+    }
+
+    if descend {
+        app_state
+            .set(GameState::NextLevel)
+            .expect("failed to set game state to NextLevel");
+    } else if key_bindings.just_pressed(&keyboard_input, InputAction::Inventory) {
         app_state
             .set(GameState::SetupInventoryScreen)
             .expect("failed to set game state to InventoryMenu");
-    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+    } else if key_bindings.just_pressed(&keyboard_input, InputAction::Cancel) {
         app_state
             .push(GameState::MainMenu)
             .expect("failed to set game state to InventoryMenu");
@@ -97,9 +171,113 @@ fn player_input(
     keyboard_input.clear();
 }
 
+/// Lets the player pass a turn. Resting regenerates some hp, but only while no monster is in
+/// sight.
+fn rest_handler(
+    mut commands: Commands,
+    rest_query: Query<Entity, With<WantsToRest>>,
+    mut player_query: Query<(&mut CombatStats, &Viewshed), With<Player>>,
+    monster_query: Query<&Position, With<Monster>>,
+) {
+    if rest_query.is_empty() {
+        return;
+    }
+    for rest_request in rest_query.iter() {
+        commands.entity(rest_request).despawn();
+    }
+
+    let (mut player_stats, viewshed) = player_query
+        .get_single_mut()
+        .expect("Found 0 or more than one player while resting");
+
+    let monster_in_sight = monster_query
+        .iter()
+        .any(|monster_pos| viewshed.visible_tiles.contains(monster_pos));
+
+    rest_turn(&mut player_stats, monster_in_sight);
+}
+
+/// Heals the player for a turn spent resting, unless a monster is in sight
+fn rest_turn(player_stats: &mut CombatStats, monster_in_sight: bool) {
+    if !monster_in_sight {
+        player_stats.heal(REST_HEAL_AMOUNT);
+    }
+}
+
+/// Why a resting player has to get up, if they have to. Hungry players can't rest, starvation
+/// would eat up everything resting heals.
+fn rest_interruption(
+    player_stats: &CombatStats,
+    hunger_state: HungerState,
+    monster_in_sight: bool,
+) -> Option<&'static str> {
+    if monster_in_sight {
+        Some("You spot a monster and stop resting.")
+    } else if hunger_state == HungerState::Hungry || hunger_state == HungerState::Starving {
+        Some("You are too hungry to rest.")
+    } else if player_stats.hp >= player_stats.max_hp {
+        Some("You feel rested.")
+    } else {
+        None
+    }
+}
+
+/// Passes turns for a resting player until they are fully healed, a monster shows up, they get
+/// hungry or any key or mouse button is pressed.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn rest(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut app_state: ResMut<State<GameState>>,
+    player_query: Query<
+        (Entity, &CombatStats, &HungerClock, &Viewshed),
+        (With<Player>, With<Resting>),
+    >,
+    monster_query: Query<&Position, With<Monster>>,
+    mut action_log: ResMut<ActionLog>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    if *app_state.current() != GameState::AwaitingActionInput {
+        return;
+    }
+    let (player, player_stats, hunger_clock, viewshed) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    let monster_in_sight = monster_query
+        .iter()
+        .any(|monster_pos| viewshed.visible_tiles.contains(monster_pos));
+
+    let stop_reason = if keyboard_input.get_just_pressed().len() > 0
+        || mouse_input.get_just_pressed().len() > 0
+    {
+        // The input itself is handled by the other input systems
+        Some("You stop resting.")
+    } else {
+        rest_interruption(player_stats, hunger_clock.state, monster_in_sight)
+    };
+
+    match stop_reason {
+        Some(reason) => {
+            action_log.entries.push(reason.to_owned());
+            commands.entity(player).remove::<Resting>();
+        }
+        None => {
+            commands.spawn_empty().insert(WantsToRest {});
+            app_state
+                .set(GameState::PlayerTurn)
+                .expect("failed to set game state in rest");
+            redraw.send(RequestRedraw);
+        }
+    }
+}
+
 /// Moves the player if no obstacle is in the way or tries to fight the obstacle, if fightable.
 /// Is only called if game state is in `GameState::PlayerTurn`.
 /// At the end of the player turn, set the game to `GameState::MonsterTurn`.
+#[allow(clippy::too_many_arguments)]
 fn player_turn(
     mut player_query: Query<(
         Entity,
@@ -109,8 +287,10 @@ fn player_turn(
         With<Player>,
     )>,
     mut combattable_query: Query<&mut CombatStats>,
+    monster_name_query: Query<&MonsterName>,
     mut map: ResMut<GameMap>,
     mut damage_tracker: ResMut<DamageTracker>,
+    mut game_rng: ResMut<GameRng>,
     mut app_state: ResMut<State<GameState>>,
     mut user_input_res: ResMut<UserInput>,
     mut action_log: ResMut<ActionLog>,
@@ -126,25 +306,30 @@ fn player_turn(
 
             let new_pos = Position { x: new_x, y: new_y };
 
-            if map.is_blocked(&new_pos) {
+            if map.cuts_corner(&player_pos, &new_pos) {
+                // Already refused in player_input
+            } else if map.is_blocked(&new_pos) {
                 if let Some(entity) = map.tile_content.get(&new_pos) {
                     if let Ok(combattable) =
                         combattable_query.get_many_mut([*entity, player_entity])
                     {
                         // We found something to hit here
-                        let player_power = combattable[1].power;
-                        SufferDamage::add_damage(
+                        let monster_name = monster_name_query
+                            .get(*entity)
+                            .map(|name| name.name.as_str())
+                            .unwrap_or(UNKNOWN_MONSTER_NAME);
+                        let outcome = melee_attack(
                             &mut damage_tracker,
-                            *entity,
-                            player_power,
                             action_log.as_mut(),
-                            true,
-                        );
-                        bevy::log::info!(
-                            "Monster has been hit with {} and has {} hp left",
-                            player_power,
-                            combattable[0].hp - player_power
+                            &mut game_rng.rng,
+                            player_entity,
+                            PLAYER_NAME,
+                            &combattable[1],
+                            *entity,
+                            monster_name,
+                            &combattable[0],
                         );
+                        bevy::log::info!("Player attacked {}: {:?}", monster_name, outcome);
                     } else {
                         bevy::log::warn!(
                             "Could not find combattable component of at least one entity"
@@ -157,8 +342,8 @@ fn player_turn(
                 map.remove_tile_content(&player_pos);
 
                 // block new position
+                map.set_tile_content(new_pos.clone(), player_entity);
                 map.set_blocked(new_pos);
-                map.set_tile_content(player_pos.clone(), player_entity);
 
                 player_pos.x = new_x;
                 player_pos.y = new_y;
@@ -181,3 +366,41 @@ fn player_turn(
             .expect("failed to set game state in try_move_player");
     }
 }
+
+#[test]
+fn resting_heals_until_interrupted() {
+    let mut player_stats = CombatStats {
+        max_hp: 10,
+        hp: 8,
+        defense: 0,
+        power: 0,
+    };
+
+    assert_eq!(
+        rest_interruption(&player_stats, HungerState::Normal, false),
+        None
+    );
+    rest_turn(&mut player_stats, false);
+    assert_eq!(player_stats.hp, 9);
+
+    // No healing with a monster in sight, and resting stops
+    assert!(rest_interruption(&player_stats, HungerState::Normal, true).is_some());
+    rest_turn(&mut player_stats, true);
+    assert_eq!(player_stats.hp, 9);
+
+    rest_turn(&mut player_stats, false);
+    rest_turn(&mut player_stats, false);
+    assert_eq!(player_stats.hp, 10);
+    assert!(rest_interruption(&player_stats, HungerState::WellFed, false).is_some());
+
+    // Starvation damage would cancel out the healing, so resting would never end
+    player_stats.hp = 5;
+    assert_eq!(
+        rest_interruption(&player_stats, HungerState::Hungry, false),
+        Some("You are too hungry to rest.")
+    );
+    assert_eq!(
+        rest_interruption(&player_stats, HungerState::Starving, false),
+        Some("You are too hungry to rest.")
+    );
+}
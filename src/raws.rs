@@ -0,0 +1,240 @@
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::{Color, Resource},
+};
+use rand::{rngs::StdRng, Rng};
+use serde::Deserialize;
+
+use crate::{
+    components::{
+        combat_stats::CombatStats,
+        consumable::Consumable,
+        damage::InflictsDamage,
+        equipment::{EquipmentSlot, Equippable},
+        experience::ExperienceReward,
+        hunger::ProvidesFood,
+        item::{AreaOfEffect, AreaShape, Confusion, Heals, Ranged, Stackable},
+    },
+    monster::MonsterBehaviour,
+    utils::random_table::RandomTable,
+};
+
+pub const RAWS_PATH: &str = "assets/raws/spawns.ron";
+
+/// Definitions of every monster and item that can be spawned, loaded from `RAWS_PATH`.
+#[derive(Resource, Deserialize, Debug)]
+pub struct Raws {
+    pub items: Vec<RawEntity>,
+    pub monsters: Vec<RawMonster>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawEntity {
+    pub name: String,
+    pub glyph: char,
+    pub color: (u8, u8, u8),
+    pub spawn_weight: SpawnWeight,
+    pub components: Vec<RawComponent>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RawMonster {
+    pub name: String,
+    pub glyph: char,
+    pub color: (u8, u8, u8),
+    pub spawn_weight: SpawnWeight,
+    pub fov: i32,
+    pub behaviour: MonsterBehaviour,
+    pub components: Vec<RawComponent>,
+    #[serde(default)]
+    pub loot: Option<LootTable>,
+}
+
+/// What a monster may leave behind when it dies
+#[derive(Deserialize, Debug, Clone)]
+pub struct LootTable {
+    /// Chance in percent that anything is dropped at all
+    pub drop_chance: i32,
+    /// Names of items in the raws, rolled for by weight
+    pub items: Vec<LootEntry>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LootEntry {
+    pub name: String,
+    pub weight: i32,
+}
+
+impl LootTable {
+    /// Returns the name of the dropped item, if anything is dropped
+    pub fn roll(&self, rng: &mut StdRng) -> Option<&str> {
+        if rng.gen_range(0..100) >= self.drop_chance {
+            return None;
+        }
+
+        let mut table = RandomTable::new();
+        for entry in &self.items {
+            table = table.add(entry.name.as_str(), entry.weight);
+        }
+        table.roll(rng).copied()
+    }
+}
+
+/// How likely an entry is to be spawned in a room, relative to the other entries
+#[derive(Deserialize, Debug, Clone)]
+pub struct SpawnWeight {
+    pub base: i32,
+    pub per_depth: i32,
+}
+
+impl SpawnWeight {
+    pub fn at_depth(&self, depth: i32) -> i32 {
+        self.base + self.per_depth * depth
+    }
+}
+
+/// A component as it is written down in the raws file
+#[derive(Deserialize, Debug, Clone)]
+pub enum RawComponent {
+    CombatStats {
+        max_hp: i32,
+        hp: i32,
+        defense: i32,
+        power: i32,
+    },
+    InflictsDamage {
+        damage: i32,
+    },
+    Ranged {
+        range: u32,
+    },
+    AreaOfEffect {
+        radius: u32,
+    },
+    AreaShape(AreaShape),
+    Confusion {
+        turns: u32,
+    },
+    Heals {
+        heal_amount: i32,
+    },
+    ExperienceReward {
+        xp: i32,
+    },
+    ProvidesFood,
+    Consumable,
+    Stackable,
+    Equippable {
+        slot: EquipmentSlot,
+        power_bonus: i32,
+        defense_bonus: i32,
+    },
+}
+
+impl Raws {
+    /// Loads the raws from disk. Fails if the file is missing or broken.
+    pub fn load(path: &str) -> Result<Raws, String> {
+        let raws = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read raws from {}: {}", path, e))?;
+
+        Raws::from_ron(&raws).map_err(|e| format!("Failed to parse raws {}: {}", path, e))
+    }
+
+    pub fn from_ron(raws: &str) -> Result<Raws, ron::Error> {
+        ron::de::from_str(raws)
+    }
+
+    pub fn get_item(&self, name: &str) -> Option<&RawEntity> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    pub fn get_monster(&self, name: &str) -> Option<&RawMonster> {
+        self.monsters.iter().find(|monster| monster.name == name)
+    }
+}
+
+pub fn raw_color(color: (u8, u8, u8)) -> Color {
+    Color::rgb_u8(color.0, color.1, color.2)
+}
+
+/// Adds all components of a raw definition to an entity
+pub fn insert_raw_components(entity: &mut EntityCommands, components: &[RawComponent]) {
+    for component in components {
+        match component.clone() {
+            RawComponent::CombatStats {
+                max_hp,
+                hp,
+                defense,
+                power,
+            } => entity.insert(CombatStats {
+                max_hp,
+                hp,
+                defense,
+                power,
+            }),
+            RawComponent::InflictsDamage { damage } => entity.insert(InflictsDamage { damage }),
+            RawComponent::Ranged { range } => entity.insert(Ranged { range }),
+            RawComponent::AreaOfEffect { radius } => entity.insert(AreaOfEffect { radius }),
+            RawComponent::AreaShape(shape) => entity.insert(shape),
+            RawComponent::Confusion { turns } => entity.insert(Confusion { turns }),
+            RawComponent::Heals { heal_amount } => entity.insert(Heals { heal_amount }),
+            RawComponent::ExperienceReward { xp } => entity.insert(ExperienceReward { xp }),
+            RawComponent::ProvidesFood => entity.insert(ProvidesFood {}),
+            RawComponent::Consumable => entity.insert(Consumable {}),
+            RawComponent::Stackable => entity.insert(Stackable {}),
+            RawComponent::Equippable {
+                slot,
+                power_bonus,
+                defense_bonus,
+            } => entity.insert(Equippable {
+                slot,
+                power_bonus,
+                defense_bonus,
+            }),
+        };
+    }
+}
+
+#[test]
+fn can_parse_spawn_raws() {
+    let raws = Raws::from_ron(include_str!("../assets/raws/spawns.ron")).unwrap();
+
+    let fireball = raws.get_item("Fireball Scroll").unwrap();
+    assert!(fireball
+        .components
+        .iter()
+        .any(|c| matches!(c, RawComponent::AreaOfEffect { radius: 4 })));
+
+    // Fireballs are rare early on, but get more common deeper down
+    assert_eq!(fireball.spawn_weight.at_depth(1), 1);
+    assert!(fireball.spawn_weight.at_depth(5) > fireball.spawn_weight.at_depth(1));
+
+    let lightning = raws.get_item("Lightning Scroll").unwrap();
+    assert!(lightning
+        .components
+        .iter()
+        .any(|c| matches!(c, RawComponent::AreaShape(AreaShape::Line))));
+
+    let dagger = raws.get_item("Dagger").unwrap();
+    assert!(dagger.components.iter().any(|c| matches!(
+        c,
+        RawComponent::Equippable {
+            slot: EquipmentSlot::Weapon,
+            ..
+        }
+    )));
+
+    let orc = raws.get_monster("Orc").unwrap();
+    assert_eq!(orc.glyph, 'o');
+    let orc_loot = orc.loot.as_ref().unwrap();
+    assert!(orc_loot
+        .items
+        .iter()
+        .all(|entry| raws.get_item(&entry.name).is_some()));
+
+    let troll = raws.get_monster("Troll").unwrap();
+    assert_eq!(troll.behaviour, MonsterBehaviour::Stationary);
+    // Trolls only show up deeper down
+    assert!(troll.spawn_weight.at_depth(1) <= 0);
+    assert!(raws.get_item("Sword of Missing Content").is_none());
+}
@@ -0,0 +1,650 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        combat_stats::CombatStats,
+        consumable::Consumable,
+        corpse::Corpse,
+        damage::{DamageTracker, InflictsDamage},
+        equipment::{Equipment, Equippable},
+        experience::Experience,
+        glyph::Glyph,
+        hunger::{HungerClock, ProvidesFood},
+        item::{AreaOfEffect, AreaShape, Confusion, Heals, Item, ItemName, Ranged, Stackable},
+        position::Position,
+    },
+    game_over::GameStats,
+    inventory::components::{Inventory, ItemStack},
+    map::{game_map::GameMap, TileType},
+    monster::{Monster, MonsterName},
+    player::Player,
+    raws::{raw_color, Raws},
+    spawner::{
+        despawn_game_entities, item_sprite_bundle, spawn_corpse, spawn_named_monster, spawn_player,
+        GameEntityFilter,
+    },
+    user_interface::ActionLog,
+    utils::rng::GameRng,
+    GameConfig, GameState,
+};
+
+pub const SAVE_FILE_PATH: &str = "savegame.ron";
+
+pub struct SaveLoadPlugin {}
+
+impl Plugin for SaveLoadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::MainMenu)
+                .with_system(save_game)
+                .with_system(load_game),
+        );
+    }
+}
+
+/// Flag component requesting the running game to be written to `SAVE_FILE_PATH`.
+#[derive(Component)]
+pub struct WantsToSaveGame {}
+
+/// Flag component requesting the game stored in `SAVE_FILE_PATH` to replace the running game.
+#[derive(Component)]
+pub struct WantsToLoadGame {}
+
+/// Everything needed to rebuild a running game. Stored as RON.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SaveGame {
+    pub map: SavedMap,
+    pub player: SavedPlayer,
+    pub monsters: Vec<SavedMonster>,
+    pub corpses: Vec<SavedCorpse>,
+    /// Items lying on the floor. Items in the inventory are stored with the player.
+    pub items: Vec<SavedItem>,
+    pub action_log: Vec<String>,
+    pub stats: GameStats,
+    pub rng: SavedRng,
+}
+
+/// The dungeon seed, which deeper levels are generated from, and the state of the gameplay RNG
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SavedRng {
+    pub seed: u64,
+    pub state: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SavedMap {
+    pub height: i32,
+    pub width: i32,
+    pub depth: i32,
+    pub tiles: HashMap<Position, TileType>,
+    pub visited_tiles: HashSet<Position>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SavedPlayer {
+    pub position: Position,
+    pub combat_stats: CombatStats,
+    pub experience: Experience,
+    pub hunger_clock: HungerClock,
+    /// The items stacked in each inventory slot, empty for a free slot
+    pub inventory: Vec<Vec<SavedItem>>,
+    /// Worn items. Their bonuses are already part of the saved stats.
+    pub equipment: Vec<SavedItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SavedMonster {
+    /// Name of the monster's definition in the raws
+    pub name: String,
+    pub position: Position,
+    pub combat_stats: CombatStats,
+    pub confusion: Option<Confusion>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SavedCorpse {
+    /// Name of the dead monster's definition in the raws
+    pub name: String,
+    pub position: Position,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SavedItem {
+    /// `None` if the item is in an inventory
+    pub position: Option<Position>,
+    pub color: [f32; 4],
+    pub glyph: Glyph,
+    pub name: Option<ItemName>,
+    pub heals: Option<Heals>,
+    pub inflicts_damage: Option<InflictsDamage>,
+    pub ranged: Option<Ranged>,
+    pub area_of_effect: Option<AreaOfEffect>,
+    pub area_shape: Option<AreaShape>,
+    pub confusion: Option<Confusion>,
+    pub consumable: bool,
+    pub stackable: bool,
+    pub provides_food: bool,
+    pub equippable: Option<Equippable>,
+}
+
+type ItemQueryItem<'a> = (
+    Option<&'a Position>,
+    &'a Sprite,
+    &'a Glyph,
+    Option<&'a ItemName>,
+    Option<&'a Heals>,
+    Option<&'a InflictsDamage>,
+    Option<&'a Ranged>,
+    Option<&'a AreaOfEffect>,
+    Option<&'a AreaShape>,
+    Option<&'a Confusion>,
+    Option<&'a Consumable>,
+    Option<&'a Stackable>,
+    Option<&'a ProvidesFood>,
+    Option<&'a Equippable>,
+);
+
+/// Writes the running game to `SAVE_FILE_PATH` and returns to the game.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn save_game(
+    mut commands: Commands,
+    save_request_query: Query<Entity, With<WantsToSaveGame>>,
+    mut app_state: ResMut<State<GameState>>,
+    map: Res<GameMap>,
+    player_query: Query<
+        (
+            &Position,
+            &CombatStats,
+            &Experience,
+            &HungerClock,
+            &Inventory,
+            &Equipment,
+        ),
+        With<Player>,
+    >,
+    monster_query: Query<
+        (&MonsterName, &Position, &CombatStats, Option<&Confusion>),
+        With<Monster>,
+    >,
+    corpse_query: Query<(&Corpse, &Position)>,
+    item_query: Query<ItemQueryItem, With<Item>>,
+    mut action_log: ResMut<ActionLog>,
+    game_stats: Res<GameStats>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    if save_request_query.is_empty() {
+        return;
+    }
+    for save_request in save_request_query.iter() {
+        commands.entity(save_request).despawn();
+    }
+
+    let (player_pos, player_stats, experience, hunger_clock, inventory, equipment) = player_query
+        .get_single()
+        .expect("Found 0 or more than one player while saving");
+
+    let mut saved_inventory = vec![];
+    for item_slot in &inventory.items {
+        let saved_stack = match item_slot {
+            Some(stack) => stack
+                .entities
+                .iter()
+                .filter_map(|item| item_query.get(*item).ok().map(save_item))
+                .collect(),
+            None => vec![],
+        };
+        saved_inventory.push(saved_stack);
+    }
+
+    let saved_equipment = equipment
+        .slots
+        .values()
+        .filter_map(|item| item_query.get(*item).ok().map(save_item))
+        .collect();
+
+    let mut saved_items = vec![];
+    for item in item_query.iter() {
+        // Items without a position are part of an inventory
+        if item.0.is_some() {
+            saved_items.push(save_item(item));
+        }
+    }
+
+    let save = SaveGame {
+        map: SavedMap {
+            height: map.height,
+            width: map.width,
+            depth: map.depth,
+            tiles: map.tiles.clone(),
+            visited_tiles: map.visited_tiles.clone(),
+        },
+        player: SavedPlayer {
+            position: player_pos.clone(),
+            combat_stats: player_stats.clone(),
+            experience: experience.clone(),
+            hunger_clock: hunger_clock.clone(),
+            inventory: saved_inventory,
+            equipment: saved_equipment,
+        },
+        monsters: monster_query
+            .iter()
+            .map(|(name, pos, stats, confusion)| SavedMonster {
+                name: name.name.clone(),
+                position: pos.clone(),
+                combat_stats: stats.clone(),
+                confusion: confusion.cloned(),
+            })
+            .collect(),
+        corpses: corpse_query
+            .iter()
+            .map(|(corpse, pos)| SavedCorpse {
+                name: corpse.name.clone(),
+                position: pos.clone(),
+            })
+            .collect(),
+        items: saved_items,
+        action_log: action_log.entries.clone(),
+        stats: game_stats.clone(),
+        rng: SavedRng {
+            seed: game_rng.seed,
+            state: game_rng.save_state(),
+        },
+    };
+
+    let result = ron::ser::to_string_pretty(&save, PrettyConfig::new())
+        .map_err(|e| e.to_string())
+        .and_then(|serialized| {
+            std::fs::write(SAVE_FILE_PATH, serialized).map_err(|e| e.to_string())
+        });
+
+    match result {
+        Ok(()) => action_log.entries.push("Game saved".to_owned()),
+        Err(e) => {
+            bevy::log::error!("Failed to save game: {}", e);
+            action_log.entries.push("Saving failed!".to_owned());
+        }
+    }
+
+    app_state.pop().expect("Popping main menu game state");
+}
+
+fn save_item(item: ItemQueryItem) -> SavedItem {
+    let (
+        position,
+        sprite,
+        glyph,
+        name,
+        heals,
+        inflicts_damage,
+        ranged,
+        aoe,
+        shape,
+        confusion,
+        consumable,
+        stackable,
+        provides_food,
+        equippable,
+    ) = item;
+
+    SavedItem {
+        position: position.cloned(),
+        color: sprite.color.as_rgba_f32(),
+        glyph: *glyph,
+        name: name.cloned(),
+        heals: heals.cloned(),
+        inflicts_damage: inflicts_damage.cloned(),
+        ranged: ranged.cloned(),
+        area_of_effect: aoe.cloned(),
+        area_shape: shape.copied(),
+        confusion: confusion.cloned(),
+        consumable: consumable.is_some(),
+        stackable: stackable.is_some(),
+        provides_food: provides_food.is_some(),
+        equippable: equippable.cloned(),
+    }
+}
+
+/// Replaces the running game with the one stored in `SAVE_FILE_PATH`.
+/// Sets the game to `GameState::MapLoaded` so the map tiles get spawned again.
+#[allow(clippy::too_many_arguments)]
+fn load_game(
+    mut commands: Commands,
+    load_request_query: Query<Entity, With<WantsToLoadGame>>,
+    mut app_state: ResMut<State<GameState>>,
+    game_entities_query: Query<Entity, GameEntityFilter>,
+    mut damage_tracker: ResMut<DamageTracker>,
+    mut action_log: ResMut<ActionLog>,
+    mut game_stats: ResMut<GameStats>,
+    mut game_rng: ResMut<GameRng>,
+    game_config: Res<GameConfig>,
+    raws: Res<Raws>,
+) {
+    if load_request_query.is_empty() {
+        return;
+    }
+    for load_request in load_request_query.iter() {
+        commands.entity(load_request).despawn();
+    }
+
+    let save = match read_save_file() {
+        Ok(save) => save,
+        Err(e) => {
+            bevy::log::error!("Failed to load game: {}", e);
+            action_log.entries.push("No saved game to load".to_owned());
+            app_state.pop().expect("Popping main menu game state");
+            return;
+        }
+    };
+
+    despawn_game_entities(&mut commands, &game_entities_query);
+    damage_tracker.0.clear();
+
+    let mut map = GameMap::new(
+        save.map.height,
+        save.map.width,
+        save.map.depth,
+        HashMap::new(),
+        save.map.visited_tiles,
+        HashSet::new(),
+        HashMap::new(),
+    );
+    for (pos, tile) in save.map.tiles {
+        if tile == TileType::Wall {
+            map.set_blocked(pos.clone());
+        }
+        map.tiles.insert(pos, tile);
+    }
+
+    let tile_properties = &game_config.tile_properties;
+    let screen_dimensions = &game_config.screen_dimensions;
+
+    let mut inventory = Inventory::new(save.player.inventory.len());
+    for (slot, saved_stack) in save.player.inventory.into_iter().enumerate() {
+        let stack_key = saved_stack
+            .first()
+            .filter(|item| item.stackable)
+            .and_then(|item| item.name.as_ref())
+            .map(|name| name.name.clone());
+        let entities: Vec<Entity> = saved_stack
+            .into_iter()
+            .map(|saved_item| spawn_saved_item(&mut commands, saved_item, &game_config))
+            .collect();
+
+        if !entities.is_empty() {
+            inventory.items[slot] = Some(ItemStack {
+                stack_key,
+                entities,
+            });
+        }
+    }
+
+    let mut equipment = Equipment::default();
+    for saved_item in save.player.equipment {
+        let slot = saved_item
+            .equippable
+            .as_ref()
+            .map(|equippable| equippable.slot);
+        let item = spawn_saved_item(&mut commands, saved_item, &game_config);
+        if let Some(slot) = slot {
+            equipment.equip(slot, item);
+        }
+    }
+
+    let player = spawn_player(
+        &mut commands,
+        save.player.position.clone(),
+        tile_properties,
+        screen_dimensions,
+        &game_config.gameplay_settings,
+    );
+    commands
+        .entity(player)
+        .insert(save.player.combat_stats)
+        .insert(save.player.experience)
+        .insert(save.player.hunger_clock)
+        .insert(inventory)
+        .insert(equipment);
+    map.set_blocked(save.player.position.clone());
+    map.set_tile_content(save.player.position, player);
+
+    for saved_monster in save.monsters {
+        let monster = match spawn_named_monster(
+            &mut commands,
+            &raws,
+            &saved_monster.name,
+            &saved_monster.position,
+            tile_properties,
+            screen_dimensions,
+        ) {
+            Some(monster) => monster,
+            // The monster's definition is gone from the raws
+            None => continue,
+        };
+        commands.entity(monster).insert(saved_monster.combat_stats);
+        if let Some(confusion) = saved_monster.confusion {
+            commands.entity(monster).insert(confusion);
+        }
+        map.set_blocked(saved_monster.position.clone());
+        map.set_tile_content(saved_monster.position, monster);
+    }
+
+    for saved_corpse in save.corpses {
+        // The corpse is colored after the monster's definition, skip it if that's gone
+        if let Some(monster) = raws.get_monster(&saved_corpse.name) {
+            spawn_corpse(
+                &mut commands,
+                &saved_corpse.name,
+                raw_color(monster.color),
+                &saved_corpse.position,
+                tile_properties,
+                screen_dimensions,
+            );
+        }
+    }
+
+    for saved_item in save.items {
+        spawn_saved_item(&mut commands, saved_item, &game_config);
+    }
+
+    commands.insert_resource(map);
+
+    *game_stats = save.stats;
+    *game_rng = GameRng::restore(save.rng.seed, save.rng.state);
+    action_log.entries = save.action_log;
+    action_log.entries.push("Game loaded".to_owned());
+
+    app_state
+        .replace(GameState::MapLoaded)
+        .expect("failed to set game state after loading");
+}
+
+fn read_save_file() -> Result<SaveGame, String> {
+    let serialized = std::fs::read_to_string(SAVE_FILE_PATH).map_err(|e| e.to_string())?;
+    ron::de::from_str(&serialized).map_err(|e| e.to_string())
+}
+
+fn spawn_saved_item(commands: &mut Commands, item: SavedItem, game_config: &GameConfig) -> Entity {
+    let [r, g, b, a] = item.color;
+    // Inventory items have no position, their sprite is only used to color the inventory slot
+    let sprite_pos = item.position.clone().unwrap_or(Position::new(0, 0));
+
+    let mut entity = commands.spawn(item_sprite_bundle(
+        Color::rgba(r, g, b, a),
+        item.glyph,
+        &sprite_pos,
+        &game_config.tile_properties,
+        &game_config.screen_dimensions,
+    ));
+    entity.insert(Item {});
+
+    match item.position {
+        Some(pos) => {
+            entity.insert(pos);
+        }
+        None => {
+            entity
+                .insert(Visibility { is_visible: false })
+                .remove::<Transform>();
+        }
+    }
+
+    if let Some(name) = item.name {
+        entity.insert(name);
+    }
+    if let Some(heals) = item.heals {
+        entity.insert(heals);
+    }
+    if let Some(inflicts_damage) = item.inflicts_damage {
+        entity.insert(inflicts_damage);
+    }
+    if let Some(ranged) = item.ranged {
+        entity.insert(ranged);
+    }
+    if let Some(aoe) = item.area_of_effect {
+        entity.insert(aoe);
+    }
+    if let Some(shape) = item.area_shape {
+        entity.insert(shape);
+    }
+    if let Some(confusion) = item.confusion {
+        entity.insert(confusion);
+    }
+    if item.consumable {
+        entity.insert(Consumable {});
+    }
+    if item.stackable {
+        entity.insert(Stackable {});
+    }
+    if item.provides_food {
+        entity.insert(ProvidesFood {});
+    }
+    if let Some(equippable) = item.equippable {
+        entity.insert(equippable);
+    }
+
+    entity.id()
+}
+
+#[test]
+fn can_roundtrip_save_game() {
+    use crate::components::{equipment::EquipmentSlot, hunger::HungerState};
+
+    let mut tiles = HashMap::new();
+    tiles.insert(Position::new(0, 0), TileType::Wall);
+    tiles.insert(Position::new(1, 0), TileType::Floor);
+
+    let fireball = SavedItem {
+        position: None,
+        color: [1.0, 0.5, 0.0, 1.0],
+        glyph: Glyph { glyph: '?' },
+        name: Some(ItemName {
+            name: "Fireball Scroll".to_owned(),
+        }),
+        heals: None,
+        inflicts_damage: Some(InflictsDamage { damage: 6 }),
+        ranged: Some(Ranged { range: 6 }),
+        area_of_effect: Some(AreaOfEffect { radius: 4 }),
+        area_shape: None,
+        confusion: None,
+        consumable: true,
+        stackable: true,
+        provides_food: false,
+        equippable: None,
+    };
+    let dagger = SavedItem {
+        position: None,
+        color: [0.7, 0.7, 0.7, 1.0],
+        glyph: Glyph { glyph: '/' },
+        name: Some(ItemName {
+            name: "Dagger".to_owned(),
+        }),
+        heals: None,
+        inflicts_damage: None,
+        ranged: None,
+        area_of_effect: None,
+        area_shape: None,
+        confusion: None,
+        consumable: false,
+        stackable: false,
+        provides_food: false,
+        equippable: Some(Equippable {
+            slot: EquipmentSlot::Weapon,
+            power_bonus: 2,
+            defense_bonus: 0,
+        }),
+    };
+
+    let save = SaveGame {
+        map: SavedMap {
+            height: 1,
+            width: 2,
+            depth: 3,
+            tiles,
+            visited_tiles: HashSet::from([Position::new(1, 0)]),
+        },
+        player: SavedPlayer {
+            position: Position::new(1, 0),
+            combat_stats: CombatStats {
+                max_hp: 100,
+                hp: 42,
+                defense: 0,
+                power: 5,
+            },
+            experience: Experience { level: 2, xp: 35 },
+            hunger_clock: HungerClock {
+                state: HungerState::Hungry,
+                duration: 12,
+            },
+            inventory: vec![vec![fireball.clone(), fireball], vec![]],
+            equipment: vec![dagger],
+        },
+        monsters: vec![],
+        corpses: vec![SavedCorpse {
+            name: "Orc".to_owned(),
+            position: Position::new(1, 0),
+        }],
+        items: vec![],
+        action_log: vec!["Adventure awaits!".to_owned()],
+        stats: GameStats {
+            turns: 120,
+            kills: 4,
+            cause_of_death: None,
+        },
+        rng: SavedRng { seed: 42, state: 7 },
+    };
+
+    let serialized = ron::ser::to_string_pretty(&save, PrettyConfig::new()).unwrap();
+    let loaded: SaveGame = ron::de::from_str(&serialized).unwrap();
+
+    assert_eq!(loaded.map.depth, 3);
+    assert_eq!(loaded.map.tiles, save.map.tiles);
+    assert_eq!(loaded.map.visited_tiles, save.map.visited_tiles);
+    assert_eq!(loaded.player.position, save.player.position);
+    assert_eq!(loaded.player.combat_stats.hp, 42);
+    assert_eq!(loaded.player.experience.level, 2);
+    assert_eq!(loaded.player.hunger_clock.state, HungerState::Hungry);
+    assert_eq!(loaded.player.inventory.len(), 2);
+
+    assert_eq!(loaded.player.inventory[0].len(), 2);
+    let item = &loaded.player.inventory[0][0];
+    assert!(item.stackable);
+    assert_eq!(item.name.as_ref().unwrap().name, "Fireball Scroll");
+    assert_eq!(item.area_of_effect.as_ref().unwrap().radius, 4);
+    assert_eq!(item.glyph.glyph, '?');
+    assert!(item.consumable);
+    let dagger = &loaded.player.equipment[0];
+    assert_eq!(
+        dagger.equippable.as_ref().unwrap().slot,
+        EquipmentSlot::Weapon
+    );
+    assert!(loaded.player.inventory[1].is_empty());
+    assert_eq!(loaded.corpses[0].name, "Orc");
+    assert_eq!(loaded.stats.turns, 120);
+    assert_eq!(loaded.action_log, save.action_log);
+    assert_eq!(loaded.rng.seed, 42);
+    assert_eq!(loaded.rng.state, 7);
+}
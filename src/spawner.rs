@@ -1,43 +1,91 @@
 use bevy::{prelude::*, utils::HashSet};
-use rand::{prelude::ThreadRng, Rng};
+use rand::{rngs::StdRng, Rng};
 
 use crate::{
     components::{
         collidable::Collidable,
         combat_stats::CombatStats,
-        item::{AreaOfEffect, Confusion, Heals, Item},
-    },
-    components::{
-        consumable::Consumable,
-        damage::InflictsDamage,
-        item::{ItemName, Ranged},
+        corpse::Corpse,
+        equipment::Equipment,
+        experience::Experience,
+        glyph::Glyph,
+        hunger::HungerClock,
+        item::{Item, ItemName},
         position::Position,
     },
     configs::game_settings::GameplaySettings,
     inventory::components::Inventory,
-    monster::{Monster, MONSTER_FOV},
+    map::Tile,
+    monster::{Monster, MonsterName},
     player::{Player, PLAYER_FOV},
-    utils::{rectangle::Rectangle, render::map_pos_to_screen_pos},
+    raws::{insert_raw_components, raw_color, Raws},
+    utils::{random_table::RandomTable, rectangle::Rectangle, render::map_pos_to_screen_pos},
     viewshed::Viewshed,
     ScreenDimensions, TileProperties,
 };
 
-const MAX_MONSTERS_PER_ROOM: usize = 2;
 const INVENTORY_SIZE: usize = 4;
 
+/// What can be found in a room at a certain depth of the dungeon. Entries are names of
+/// definitions in the raws.
+struct SpawnTable<'a> {
+    max_monsters: usize,
+    monsters: RandomTable<&'a str>,
+    items: RandomTable<&'a str>,
+}
+
+/// Deeper levels have more monsters. Which monsters and items show up is decided by the spawn
+/// weights in the raws.
+fn spawn_table_for_depth(raws: &Raws, depth: i32) -> SpawnTable<'_> {
+    let mut monsters = RandomTable::new();
+    for monster in &raws.monsters {
+        monsters = monsters.add(monster.name.as_str(), monster.spawn_weight.at_depth(depth));
+    }
+
+    let mut items = RandomTable::new();
+    for item in &raws.items {
+        items = items.add(item.name.as_str(), item.spawn_weight.at_depth(depth));
+    }
+
+    SpawnTable {
+        max_monsters: 1 + depth.max(1) as usize,
+        monsters,
+        items,
+    }
+}
+
+/// Matches every entity that belongs to a running game and has to go when a new one starts
+pub type GameEntityFilter = Or<(
+    With<Tile>,
+    With<Player>,
+    With<Monster>,
+    With<Item>,
+    With<Corpse>,
+)>;
+
+/// Despawns the map tiles, player, monsters and items of the running game
+pub fn despawn_game_entities(
+    commands: &mut Commands,
+    game_entities_query: &Query<Entity, GameEntityFilter>,
+) {
+    for entity in game_entities_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
 pub fn spawn_player(
     commands: &mut Commands,
     pos: Position,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
     gameplay_settings: &GameplaySettings,
-) {
+) -> Entity {
     let scaled_tilesize = tile_properties.get_scaled_tile_size();
     commands
         .spawn_empty()
         .insert(SpriteBundle {
             sprite: Sprite {
-                color: Color::rgb_u8(0, 163, 204).into(),
+                color: Color::rgb_u8(0, 163, 204),
                 custom_size: Some(Vec2::new(scaled_tilesize, scaled_tilesize)),
                 ..Default::default()
             },
@@ -70,31 +118,44 @@ pub fn spawn_player(
             dirty: true,
         })
         .insert(Player {})
+        .insert(Experience::new())
+        .insert(HungerClock::well_fed())
         .insert(Collidable {})
-        .insert(Inventory::new(INVENTORY_SIZE));
+        .insert(Inventory::new(INVENTORY_SIZE))
+        .insert(Equipment::default())
+        .id()
 }
 
 pub fn spawn_room(
     commands: &mut Commands,
     room: &Rectangle,
-    rng: &mut ThreadRng,
+    depth: i32,
+    rng: &mut StdRng,
+    raws: &Raws,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-    gameplay_settings: &GameplaySettings,
 ) {
     let mut blocked_positions: HashSet<Position> = HashSet::new();
+    let spawn_table = spawn_table_for_depth(raws, depth);
 
-    let monster_count = rng.gen_range(0..=MAX_MONSTERS_PER_ROOM);
+    // Leave some room to move, even deep down in the dungeon
+    let max_monsters = spawn_table
+        .max_monsters
+        .min((room.width() * room.height()) as usize / 2);
+    let monster_count = rng.gen_range(0..=max_monsters);
     for _ in 0..monster_count {
         match try_find_unblocked_position_in_room(room, &blocked_positions, rng) {
             Some(pos) => {
-                spawn_monster(
-                    commands,
-                    &pos,
-                    tile_properties,
-                    screen_dimensions,
-                    gameplay_settings,
-                );
+                if let Some(name) = spawn_table.monsters.roll(rng) {
+                    spawn_named_monster(
+                        commands,
+                        raws,
+                        name,
+                        &pos,
+                        tile_properties,
+                        screen_dimensions,
+                    );
+                }
                 blocked_positions.insert(pos);
             }
             None => {
@@ -105,18 +166,15 @@ pub fn spawn_room(
 
     match try_find_unblocked_position_in_room(room, &blocked_positions, rng) {
         Some(pos) => {
-            let item_rng: u32 = rng.gen_range(0..=3);
-            match item_rng {
-                0 => spawn_health_pot(
+            if let Some(name) = spawn_table.items.roll(rng) {
+                spawn_named_item(
                     commands,
+                    raws,
+                    name,
                     &pos,
                     tile_properties,
                     screen_dimensions,
-                    gameplay_settings,
-                ),
-                1 => spawn_magic_missle_scroll(commands, &pos, tile_properties, screen_dimensions),
-                2 => spawn_fireball_scroll(commands, &pos, tile_properties, screen_dimensions),
-                _ => spawn_confusion_scroll(commands, &pos, tile_properties, screen_dimensions),
+                );
             }
             blocked_positions.insert(pos);
         }
@@ -126,19 +184,30 @@ pub fn spawn_room(
     }
 }
 
-pub fn spawn_monster(
+/// Spawns the monster defined in the raws under `name`. Returns `None` if there is no such
+/// monster.
+pub fn spawn_named_monster(
     commands: &mut Commands,
+    raws: &Raws,
+    name: &str,
     pos: &Position,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-    gameplay_settings: &GameplaySettings,
-) {
+) -> Option<Entity> {
+    let raw_monster = match raws.get_monster(name) {
+        Some(raw_monster) => raw_monster,
+        None => {
+            bevy::log::error!("No monster named {} in the raws", name);
+            return None;
+        }
+    };
+
     let scaled_tile_size = tile_properties.get_scaled_tile_size();
-    commands
-        .spawn_empty()
+    let mut entity = commands.spawn_empty();
+    entity
         .insert(SpriteBundle {
             sprite: Sprite {
-                color: Color::rgb_u8(204, 41, 0).into(),
+                color: raw_color(raw_monster.color),
                 custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
                 ..Default::default()
             },
@@ -158,208 +227,154 @@ pub fn spawn_monster(
             },
             ..Default::default()
         })
-        .insert(Position {
-            x: pos.x as i32,
-            y: pos.y as i32,
+        .insert(Position { x: pos.x, y: pos.y })
+        .insert(Glyph {
+            glyph: raw_monster.glyph,
         })
-        .insert(CombatStats {
-            hp: gameplay_settings.monster_starting_health,
-            max_hp: gameplay_settings.monster_starting_health,
-            defense: 0,
-            power: 2,
+        .insert(MonsterName {
+            name: raw_monster.name.clone(),
         })
+        .insert(raw_monster.behaviour.clone())
         .insert(Viewshed {
             visible_tiles: vec![],
-            range: MONSTER_FOV,
+            range: raw_monster.fov,
             dirty: true,
         })
         .insert(Collidable {})
         .insert(Monster {});
+    insert_raw_components(&mut entity, &raw_monster.components);
+
+    Some(entity.id())
 }
 
-pub fn spawn_health_pot(
+/// Spawns the item defined in the raws under `name` on the floor. Returns `None` if there is no
+/// such item.
+pub fn spawn_named_item(
     commands: &mut Commands,
+    raws: &Raws,
+    name: &str,
     pos: &Position,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-    gameplay_settings: &GameplaySettings,
-) {
-    let scaled_tile_size = tile_properties.get_scaled_tile_size();
-    commands
-        .spawn_empty()
-        .insert(SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(34, 139, 34).into(),
-                custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
-                ..Default::default()
-            },
-            transform: Transform {
-                translation: map_pos_to_screen_pos(
-                    pos,
-                    tile_properties.item_z,
-                    tile_properties.tile_size,
-                    screen_dimensions,
-                ),
-                scale: Vec3::new(
-                    tile_properties.tile_scale,
-                    tile_properties.tile_scale,
-                    tile_properties.item_z,
-                ),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Position {
-            x: pos.x as i32,
-            y: pos.y as i32,
-        })
+) -> Option<Entity> {
+    let raw_item = match raws.get_item(name) {
+        Some(raw_item) => raw_item,
+        None => {
+            bevy::log::error!("No item named {} in the raws", name);
+            return None;
+        }
+    };
+
+    let mut entity = commands.spawn(item_sprite_bundle(
+        raw_color(raw_item.color),
+        Glyph {
+            glyph: raw_item.glyph,
+        },
+        pos,
+        tile_properties,
+        screen_dimensions,
+    ));
+    entity
+        .insert(Position { x: pos.x, y: pos.y })
         .insert(Item {})
         .insert(ItemName {
-            name: "Health Potion".to_owned(),
-        })
-        .insert(Heals {
-            heal_amount: gameplay_settings.health_potion_heal_amount,
-        })
-        .insert(Consumable {});
+            name: raw_item.name.clone(),
+        });
+    insert_raw_components(&mut entity, &raw_item.components);
+
+    Some(entity.id())
 }
 
-pub fn spawn_magic_missle_scroll(
+/// Spawns the remains of a monster. Corpses are drawn in a darker shade of the monster's color,
+/// below any items lying on the same tile.
+pub fn spawn_corpse(
     commands: &mut Commands,
+    name: &str,
+    monster_color: Color,
     pos: &Position,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-) {
+) -> Entity {
     let scaled_tile_size = tile_properties.get_scaled_tile_size();
+    let [r, g, b, a] = monster_color.as_rgba_f32();
     commands
-        .spawn_empty()
-        .insert(SpriteBundle {
+        .spawn(SpriteBundle {
             sprite: Sprite {
-                color: Color::rgb_u8(227, 23, 224).into(),
-                custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
+                color: Color::rgba(r * 0.5, g * 0.5, b * 0.5, a),
+                custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size) * 0.6),
                 ..Default::default()
             },
             transform: Transform {
                 translation: map_pos_to_screen_pos(
                     pos,
-                    tile_properties.item_z,
+                    tile_properties.corpse_z,
                     tile_properties.tile_size,
                     screen_dimensions,
                 ),
                 scale: Vec3::new(
                     tile_properties.tile_scale,
                     tile_properties.tile_scale,
-                    tile_properties.item_z,
+                    tile_properties.corpse_z,
                 ),
                 ..Default::default()
             },
             ..Default::default()
         })
-        .insert(Position {
-            x: pos.x as i32,
-            y: pos.y as i32,
-        })
-        .insert(Item {})
-        .insert(ItemName {
-            name: "Magic Missile Scroll".to_owned(),
+        .insert(pos.clone())
+        .insert(Corpse {
+            name: name.to_owned(),
         })
-        .insert(InflictsDamage { damage: 8 })
-        .insert(Ranged { range: 6 })
-        .insert(Consumable {});
+        .id()
 }
 
-pub fn spawn_fireball_scroll(
-    commands: &mut Commands,
+/// Builds the sprite and glyph for an item lying on the floor at `pos`
+pub fn item_sprite_bundle(
+    color: Color,
+    glyph: Glyph,
     pos: &Position,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-) {
+) -> (SpriteBundle, Glyph) {
     let scaled_tile_size = tile_properties.get_scaled_tile_size();
-    commands
-        .spawn_empty()
-        .insert(SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(255, 106, 48).into(),
-                custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
-                ..Default::default()
-            },
-            transform: Transform {
-                translation: map_pos_to_screen_pos(
-                    pos,
-                    tile_properties.item_z,
-                    tile_properties.tile_size,
-                    screen_dimensions,
-                ),
-                scale: Vec3::new(
-                    tile_properties.tile_scale,
-                    tile_properties.tile_scale,
-                    tile_properties.item_z,
-                ),
-                ..Default::default()
-            },
+    let sprite = SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
             ..Default::default()
-        })
-        .insert(Position {
-            x: pos.x as i32,
-            y: pos.y as i32,
-        })
-        .insert(Item {})
-        .insert(ItemName {
-            name: "Fireball Scroll".to_owned(),
-        })
-        .insert(InflictsDamage { damage: 6 })
-        .insert(Ranged { range: 6 })
-        .insert(AreaOfEffect { radius: 4 })
-        .insert(Consumable {});
+        },
+        transform: item_transform(pos, tile_properties, screen_dimensions),
+        ..Default::default()
+    };
+
+    (sprite, glyph)
 }
 
-pub fn spawn_confusion_scroll(
-    commands: &mut Commands,
+/// Transform of an item lying on the floor at `pos`
+pub fn item_transform(
     pos: &Position,
     tile_properties: &TileProperties,
     screen_dimensions: &ScreenDimensions,
-) {
-    let scaled_tile_size = tile_properties.get_scaled_tile_size();
-    commands
-        .spawn_empty()
-        .insert(SpriteBundle {
-            sprite: Sprite {
-                color: Color::rgb_u8(66, 164, 245).into(),
-                custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
-                ..Default::default()
-            },
-            transform: Transform {
-                translation: map_pos_to_screen_pos(
-                    pos,
-                    tile_properties.item_z,
-                    tile_properties.tile_size,
-                    screen_dimensions,
-                ),
-                scale: Vec3::new(
-                    tile_properties.tile_scale,
-                    tile_properties.tile_scale,
-                    tile_properties.item_z,
-                ),
-                ..Default::default()
-            },
-            ..Default::default()
-        })
-        .insert(Position {
-            x: pos.x as i32,
-            y: pos.y as i32,
-        })
-        .insert(Item {})
-        .insert(ItemName {
-            name: "Confusion Scroll".to_owned(),
-        })
-        .insert(Confusion { turns: 3 })
-        .insert(Ranged { range: 6 })
-        .insert(Consumable {});
+) -> Transform {
+    Transform {
+        translation: map_pos_to_screen_pos(
+            pos,
+            tile_properties.item_z,
+            tile_properties.tile_size,
+            screen_dimensions,
+        ),
+        scale: Vec3::new(
+            tile_properties.tile_scale,
+            tile_properties.tile_scale,
+            tile_properties.item_z,
+        ),
+        ..Default::default()
+    }
 }
 
 fn try_find_unblocked_position_in_room(
     room: &Rectangle,
     blocked_positions: &HashSet<Position>,
-    rng: &mut ThreadRng,
+    rng: &mut StdRng,
 ) -> Option<Position> {
     let pos_count = room.width() * room.height();
 
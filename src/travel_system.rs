@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use bevy::{prelude::*, window::RequestRedraw};
+
+use crate::{
+    components::{position::Position, user_input::UserInput},
+    map::{game_map::GameMap, MainCamera},
+    monster::Monster,
+    player::Player,
+    user_interface::ActionLog,
+    utils::render::{cursor_world_pos, screen_pos_to_map_pos},
+    viewshed::{spot_new_monsters, Viewshed, PLAYER_VIEWSHED_LABEL},
+    GameConfig, GameState,
+};
+
+const TRAVEL_LABEL: &str = "travel";
+
+pub struct TravelPlugin {}
+
+impl Plugin for TravelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            travel
+                .label(TRAVEL_LABEL)
+                .after(PLAYER_VIEWSHED_LABEL)
+                .before("await_input"),
+        );
+        app.add_system(click_to_travel.after(TRAVEL_LABEL).before("await_input"));
+    }
+}
+
+/// Component that flags the player walking along a path picked with the mouse, one turn per
+/// frame.
+#[derive(Component)]
+pub struct Travelling {
+    /// Remaining steps, the next one first
+    pub path: Vec<Position>,
+    /// Monsters in sight on the previous step, `None` before the first step
+    pub monsters_in_sight: Option<HashSet<Entity>>,
+}
+
+/// Starts travelling to a visited tile when it is clicked.
+#[allow(clippy::too_many_arguments)]
+fn click_to_travel(
+    mut commands: Commands,
+    mouse_input: Res<Input<MouseButton>>,
+    app_state: Res<State<GameState>>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    player_query: Query<(Entity, &Position), With<Player>>,
+    map: Res<GameMap>,
+    game_config: Res<GameConfig>,
+    mut action_log: ResMut<ActionLog>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    if *app_state.current() != GameState::AwaitingActionInput
+        || !mouse_input.just_pressed(MouseButton::Left)
+    {
+        return;
+    }
+    let (player, player_pos) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    let (camera, camera_transform) = camera_query.single();
+    let window = windows.get_primary().expect("No primary window found");
+    let world_pos = match cursor_world_pos(window, camera, camera_transform) {
+        Some(world_pos) => world_pos,
+        None => return,
+    };
+    let destination = screen_pos_to_map_pos(
+        world_pos,
+        game_config.tile_properties.tile_size,
+        &game_config.screen_dimensions,
+    );
+
+    if destination == *player_pos || !map.visited_tiles.contains(&destination) {
+        return;
+    }
+
+    match find_travel_path(&map, player_pos, &destination) {
+        Some(path) => {
+            commands.entity(player).insert(Travelling {
+                path,
+                monsters_in_sight: None,
+            });
+            redraw.send(RequestRedraw);
+        }
+        None => action_log
+            .entries
+            .push("You don't know a way there.".to_owned()),
+    }
+}
+
+/// Takes the next step along the travel path. Stops when a monster comes into view, the path is
+/// blocked, the destination is reached or any key or mouse button is pressed.
+#[allow(clippy::too_many_arguments)]
+fn travel(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut app_state: ResMut<State<GameState>>,
+    mut player_query: Query<(Entity, &Position, &Viewshed, &mut Travelling), With<Player>>,
+    monster_query: Query<(Entity, &Position), With<Monster>>,
+    map: Res<GameMap>,
+    mut user_input_res: ResMut<UserInput>,
+    mut action_log: ResMut<ActionLog>,
+    mut redraw: EventWriter<RequestRedraw>,
+) {
+    if *app_state.current() != GameState::AwaitingActionInput {
+        return;
+    }
+    let (player, player_pos, viewshed, mut travelling) = match player_query.get_single_mut() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+
+    // Monsters that were in sight already when travelling started don't stop it
+    let monsters_in_sight = monster_query
+        .iter()
+        .filter(|(_, monster_pos)| viewshed.visible_tiles.contains(monster_pos))
+        .map(|(monster, _)| monster)
+        .collect();
+    let spotted_monster = spot_new_monsters(&mut travelling.monsters_in_sight, monsters_in_sight);
+
+    let mut stop_reason = None;
+    if keyboard_input.get_just_pressed().len() > 0 || mouse_input.get_just_pressed().len() > 0 {
+        // The input itself is handled by the other input systems
+    } else if spotted_monster {
+        stop_reason = Some("You spot a monster and stop.");
+    } else if travelling
+        .path
+        .first()
+        .is_some_and(|next| map.is_blocked(next))
+    {
+        stop_reason = Some("Something blocks your way.");
+    } else if !travelling.path.is_empty() {
+        let step = travelling.path.remove(0);
+        user_input_res.x = step.x - player_pos.x;
+        user_input_res.y = step.y - player_pos.y;
+
+        app_state
+            .set(GameState::PlayerTurn)
+            .expect("failed to set game state in travel");
+        redraw.send(RequestRedraw);
+        return;
+    }
+
+    if let Some(reason) = stop_reason {
+        action_log.entries.push(reason.to_owned());
+    }
+    commands.entity(player).remove::<Travelling>();
+}
+
+/// Finds the steps from start to destination over traversable tiles the player has seen
+fn find_travel_path(
+    map: &GameMap,
+    start: &Position,
+    destination: &Position,
+) -> Option<Vec<Position>> {
+    let (path, _) = pathfinding::directed::astar::astar(
+        start,
+        |pos| {
+            map.get_traversable_neighbours_with_distance(pos)
+                .into_iter()
+                .filter(|(neighbour, _)| map.visited_tiles.contains(neighbour))
+        },
+        |pos| pos.get_step_distance(destination),
+        |pos| pos == destination,
+    )?;
+
+    Some(path.into_iter().skip(1).collect())
+}
+
+#[test]
+fn travel_path_avoids_unknown_and_blocked_tiles() {
+    use std::collections::{HashMap, HashSet};
+
+    // A 3x3 room with a pillar in the middle and the top right corner unexplored
+    let mut visited_tiles = HashSet::new();
+    for x in 0..3 {
+        for y in 0..3 {
+            visited_tiles.insert(Position::new(x, y));
+        }
+    }
+    visited_tiles.remove(&Position::new(2, 2));
+    let map = GameMap::new(
+        3,
+        3,
+        1,
+        HashMap::new(),
+        visited_tiles,
+        HashSet::from([Position::new(1, 1)]),
+        HashMap::new(),
+    );
+
+    let path = find_travel_path(&map, &Position::new(0, 0), &Position::new(2, 1)).unwrap();
+    assert_eq!(path.last(), Some(&Position::new(2, 1)));
+    assert!(!path.contains(&Position::new(1, 1)));
+    assert!(!path.contains(&Position::new(0, 0)));
+
+    assert!(find_travel_path(&map, &Position::new(0, 0), &Position::new(2, 2)).is_none());
+}
@@ -1,23 +1,43 @@
-use std::collections::HashSet;
-
-use bevy::{ecs::system::EntityCommands, prelude::*};
-use ron::de;
+use bevy::{ecs::system::EntityCommands, prelude::*, window::RequestRedraw};
 
 use crate::{
-    components::{combat_stats::CombatStats, item::AreaOfEffect, position::Position},
+    area_of_effect::affected_tiles,
+    components::{
+        combat_stats::CombatStats,
+        equipment::{Equipment, EquipmentSlot},
+        experience::Experience,
+        hunger::{HungerClock, HungerState},
+        item::{AreaOfEffect, AreaShape, Item, ItemName, UNKNOWN_ITEM_NAME},
+        position::Position,
+    },
+    configs::key_bindings::{InputAction, KeyBindings},
     inventory::components::WantsToUseItem,
     map::{game_map::GameMap, MainCamera, Tile},
+    monster::{Monster, MonsterName},
     player::Player,
-    utils::render::map_pos_to_screen_pos,
-    viewshed::{generate_viewshed, Viewshed},
+    utils::{
+        input_utils::get_movement_input,
+        render::{cursor_world_pos, map_pos_to_screen_pos, screen_pos_to_map_pos},
+    },
+    viewshed::Viewshed,
     GameConfig, GameState, ScreenDimensions, TileProperties,
 };
 
 const ACTION_LOG_MAX_LINES: usize = 6;
 
+const XP_BAR_WIDTH_PX: f32 = 300.0;
+
 const TARGETING_MODE_TILE_COLOR: Color = Color::rgba(242.0, 36.0, 139.0, 0.05);
 
 const TARGETING_MODE_SELECTION_COLOR: Color = Color::BEIGE;
+
+const TARGETING_MODE_Z: f32 = 10.0;
+
+const BLAST_PREVIEW_COLOR: Color = Color::rgba(1.0, 0.5, 0.0, 0.3);
+
+const BLAST_PREVIEW_VICTIM_COLOR: Color = Color::rgba(1.0, 0.0, 0.0, 0.6);
+
+const BLAST_PREVIEW_Z: f32 = 11.0;
 #[derive(Clone, Resource, Default, Debug)]
 pub struct ActionLog {
     pub entries: Vec<String>,
@@ -30,14 +50,28 @@ impl Plugin for UIPlugin {
             .add_system_set(
                 SystemSet::on_update(GameState::Render)
                     .with_system(render_ui)
+                    .with_system(render_character_sheet)
+                    .with_system(render_experience_bar)
+                    .with_system(render_hunger_status)
                     .after("render_map"),
             )
             .add_system_set(
-                SystemSet::on_enter(GameState::AwaitingActionInput).with_system(render_ui),
+                SystemSet::on_enter(GameState::AwaitingActionInput)
+                    .with_system(render_ui)
+                    .with_system(render_character_sheet)
+                    .with_system(render_experience_bar)
+                    .with_system(render_hunger_status),
             )
             .add_system_set(
-                SystemSet::on_update(GameState::Targeting).with_system(render_target_mode),
-            );
+                SystemSet::on_update(GameState::Targeting)
+                    .with_system(move_targeting_cursor.before("render_target_mode"))
+                    .with_system(render_target_mode.label("render_target_mode"))
+                    .with_system(render_blast_preview.after("render_target_mode")),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Targeting).with_system(clear_blast_preview),
+            )
+            .add_system(render_tooltip);
     }
 }
 
@@ -50,18 +84,48 @@ pub struct HealthText {}
 #[derive(Component)]
 pub struct ActionLogText {}
 
-///
+#[derive(Component)]
+pub struct DepthText {}
+
+/// The filled part of the xp bar, showing the progress towards the next level
+#[derive(Component)]
+pub struct ExperienceBar {}
+
+#[derive(Component)]
+pub struct ExperienceText {}
+
+#[derive(Component)]
+pub struct HungerText {}
+
+/// Text showing the player's power, defense and worn equipment
+#[derive(Component)]
+pub struct CharacterSheetText {}
+
+/// Box following the mouse that describes the hovered tile
+#[derive(Component)]
+pub struct Tooltip {}
+
+#[derive(Component)]
+pub struct TooltipText {}
+
+/// Stores the item being aimed and its range while the game is in `GameState::Targeting`
 #[derive(Component)]
 pub struct TargetingModeContext {
     pub range: u32,
     pub item: Entity,
+    /// Tile the keyboard targeting cursor is on, placed when targeting starts
+    pub cursor: Option<Position>,
 }
 
 #[derive(Component)]
 pub struct TargetingTile {}
 
+/// Overlay marking a tile the aimed area of effect item would hit
+#[derive(Component)]
+pub struct BlastPreviewTile {}
+
 #[derive(Resource)]
-pub struct UIFont(Handle<Font>);
+pub struct UIFont(pub Handle<Font>);
 
 fn setup(mut commands: Commands, asset_server: Res<AssetServer>, game_config: Res<GameConfig>) {
     let font_handle: Handle<Font> = asset_server.load("fonts/EduVICWANTBeginner-Regular.ttf");
@@ -91,7 +155,45 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, game_config: Re
         game_config.gameplay_settings.player_starting_health as f32,
     );
 
-    spawn_action_log(&mut commands_builder, font_handle);
+    spawn_experience_bar(&mut commands_builder, font_handle.clone());
+
+    spawn_hunger_text(&mut commands_builder, font_handle.clone());
+
+    spawn_character_sheet(&mut commands_builder, font_handle.clone());
+
+    spawn_action_log(&mut commands_builder, font_handle.clone());
+
+    spawn_tooltip(&mut commands, font_handle);
+}
+
+fn spawn_tooltip(commands: &mut Commands, text_font: Handle<Font>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.8).into(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(Tooltip {})
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle {
+                    text: Text::from_section(
+                        "",
+                        TextStyle {
+                            font: text_font,
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                })
+                .insert(TooltipText {});
+        });
 }
 
 fn spawn_health_bar(
@@ -138,6 +240,144 @@ fn spawn_health_bar(
     });
 }
 
+fn spawn_experience_bar(commands: &mut EntityCommands, text_font: Handle<Font>) {
+    commands.with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(XP_BAR_WIDTH_PX), Val::Percent(10.0)),
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(50.0),
+                        top: Val::Percent(8.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                background_color: Color::rgb(0.2, 0.2, 0.2).into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(0.0), Val::Percent(100.0)),
+                            position_type: PositionType::Absolute,
+                            ..default()
+                        },
+                        background_color: Color::GOLD.into(),
+                        ..default()
+                    })
+                    .insert(ExperienceBar {});
+                parent
+                    .spawn(TextBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Px(5.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        text: Text::from_section(
+                            "",
+                            TextStyle {
+                                font: text_font.clone(),
+                                font_size: 16.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        ..default()
+                    })
+                    .insert(ExperienceText {});
+                spawn_depth_text(parent, text_font);
+            });
+    });
+}
+
+/// Depth of the current level, shown left of the xp bar
+fn spawn_depth_text(parent: &mut ChildBuilder, text_font: Handle<Font>) {
+    parent
+        .spawn(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Percent(100.0),
+                    ..default()
+                },
+                margin: UiRect {
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::from_section(
+                "Depth: 1",
+                TextStyle {
+                    font: text_font,
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+            ..default()
+        })
+        .insert(DepthText {});
+}
+
+fn spawn_hunger_text(commands: &mut EntityCommands, text_font: Handle<Font>) {
+    commands.with_children(|parent| {
+        parent
+            .spawn(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(65.0),
+                        top: Val::Percent(50.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: text_font,
+                        font_size: 27.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                ..default()
+            })
+            .insert(HungerText {});
+    });
+}
+
+fn spawn_character_sheet(commands: &mut EntityCommands, text_font: Handle<Font>) {
+    commands.with_children(|parent| {
+        parent
+            .spawn(TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(80.0),
+                        top: Val::Percent(5.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: text_font,
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                ..default()
+            })
+            .insert(CharacterSheetText {});
+    });
+}
+
 fn spawn_action_log(commands: &mut EntityCommands, text_font: Handle<Font>) {
     commands.with_children(|parent| {
         parent
@@ -178,13 +418,25 @@ fn spawn_action_log(commands: &mut EntityCommands, text_font: Handle<Font>) {
     });
 }
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn render_ui(
     player_query: Query<&CombatStats, With<Player>>,
     mut healthbar_query: Query<&mut Style, With<HealthBar>>,
-    mut healthtext_query: Query<&mut Text, (With<HealthText>, Without<ActionLogText>)>,
-    mut actionlogtext_query: Query<&mut Text, (With<ActionLogText>, Without<HealthText>)>,
+    mut healthtext_query: Query<
+        &mut Text,
+        (With<HealthText>, Without<ActionLogText>, Without<DepthText>),
+    >,
+    mut actionlogtext_query: Query<
+        &mut Text,
+        (With<ActionLogText>, Without<HealthText>, Without<DepthText>),
+    >,
+    mut depthtext_query: Query<
+        &mut Text,
+        (With<DepthText>, Without<HealthText>, Without<ActionLogText>),
+    >,
     action_log: Res<ActionLog>,
     default_font: Res<UIFont>,
+    map: Res<GameMap>,
 ) {
     let player = player_query
         .get_single()
@@ -203,6 +455,12 @@ fn render_ui(
     // We only care for the first section
     healthtext.sections[0].value = format!("{}/{}", player.hp, player.max_hp);
 
+    let mut depthtext = depthtext_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one Depth text entity while rendering UI");
+
+    depthtext.sections[0].value = format!("Depth: {}", map.depth);
+
     let actionlogtext = actionlogtext_query
         .get_single_mut()
         .expect("Found more or less than exactly one Action log text entity while rendering UI");
@@ -210,6 +468,190 @@ fn render_ui(
     render_action_log(actionlogtext, action_log, default_font);
 }
 
+fn render_experience_bar(
+    player_query: Query<&Experience, With<Player>>,
+    mut experience_bar_query: Query<&mut Style, With<ExperienceBar>>,
+    mut experience_text_query: Query<&mut Text, With<ExperienceText>>,
+    game_config: Res<GameConfig>,
+) {
+    let experience = player_query
+        .get_single()
+        .expect("Got more or less than exactly one Player entity while rendering UI");
+
+    let mut experience_bar = experience_bar_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one xp bar while rendering UI");
+
+    let mut experience_text = experience_text_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one xp text while rendering UI");
+
+    let thresholds = &game_config.leveling_settings.xp_thresholds;
+    let (progress, text) = match experience.next_level_xp(thresholds) {
+        Some(next_level_xp) => {
+            let level_start_xp = match experience.level {
+                1 => 0,
+                level => thresholds[level as usize - 2],
+            };
+            let progress =
+                (experience.xp - level_start_xp) as f32 / (next_level_xp - level_start_xp) as f32;
+            (
+                progress,
+                format!(
+                    "Level {}  {}/{} xp",
+                    experience.level, experience.xp, next_level_xp
+                ),
+            )
+        }
+        None => (
+            1.0,
+            format!("Level {}  {} xp", experience.level, experience.xp),
+        ),
+    };
+
+    experience_bar.size.width = Val::Px(progress.clamp(0.0, 1.0) * XP_BAR_WIDTH_PX);
+    // We only care for the first section
+    experience_text.sections[0].value = text;
+}
+
+fn render_hunger_status(
+    player_query: Query<&HungerClock, With<Player>>,
+    mut hunger_text_query: Query<&mut Text, With<HungerText>>,
+) {
+    let hunger_clock = player_query
+        .get_single()
+        .expect("Got more or less than exactly one Player entity while rendering UI");
+
+    let mut hunger_text = hunger_text_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one hunger text while rendering UI");
+
+    // We only care for the first section
+    hunger_text.sections[0].value = hunger_clock.state.label().to_owned();
+    hunger_text.sections[0].style.color = match hunger_clock.state {
+        HungerState::WellFed => Color::GREEN,
+        HungerState::Normal => Color::WHITE,
+        HungerState::Hungry => Color::ORANGE,
+        HungerState::Starving => Color::RED,
+    };
+}
+
+/// Shows what is on the visible tile under the mouse: the tile itself, monsters with their hp
+/// and items lying there.
+#[allow(clippy::too_many_arguments)]
+fn render_tooltip(
+    app_state: Res<State<GameState>>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    player_query: Query<&Viewshed, With<Player>>,
+    monster_query: Query<(&Position, &MonsterName, &CombatStats), With<Monster>>,
+    item_query: Query<(&Position, Option<&ItemName>), With<Item>>,
+    map: Res<GameMap>,
+    game_config: Res<GameConfig>,
+    mut tooltip_query: Query<(&mut Style, &mut Visibility), With<Tooltip>>,
+    mut tooltip_text_query: Query<&mut Text, With<TooltipText>>,
+) {
+    let (mut tooltip_style, mut tooltip_visibility) = tooltip_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one tooltip");
+    tooltip_visibility.is_visible = false;
+
+    if !matches!(
+        app_state.current(),
+        GameState::AwaitingActionInput | GameState::Targeting
+    ) {
+        return;
+    }
+    let viewshed = match player_query.get_single() {
+        Ok(viewshed) => viewshed,
+        Err(_) => return,
+    };
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let (camera, camera_transform) = camera_query.single();
+    let (mouse_pos, world_pos) = match (
+        window.cursor_position(),
+        cursor_world_pos(window, camera, camera_transform),
+    ) {
+        (Some(mouse_pos), Some(world_pos)) => (mouse_pos, world_pos),
+        _ => return,
+    };
+
+    let hovered = screen_pos_to_map_pos(
+        world_pos,
+        game_config.tile_properties.tile_size,
+        &game_config.screen_dimensions,
+    );
+    if !viewshed.visible_tiles.contains(&hovered) {
+        return;
+    }
+    let tile_type = match map.tiles.get(&hovered) {
+        Some(tile_type) => tile_type,
+        None => return,
+    };
+
+    let mut lines = vec![tile_type.label().to_owned()];
+    for (_, name, stats) in monster_query.iter().filter(|(pos, ..)| **pos == hovered) {
+        lines.push(format!("{} ({}/{} hp)", name.name, stats.hp, stats.max_hp));
+    }
+    for (_, name) in item_query.iter().filter(|(pos, _)| **pos == hovered) {
+        lines.push(
+            name.map(|name| name.name.as_str())
+                .unwrap_or(UNKNOWN_ITEM_NAME)
+                .to_owned(),
+        );
+    }
+
+    let mut tooltip_text = tooltip_text_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one tooltip text");
+    tooltip_text.sections[0].value = lines.join("\n");
+
+    // The cursor position starts at the bottom left of the window, just like UI positions
+    tooltip_style.position = UiRect {
+        left: Val::Px(mouse_pos.x + 16.0),
+        bottom: Val::Px(mouse_pos.y + 16.0),
+        ..default()
+    };
+    tooltip_visibility.is_visible = true;
+}
+
+fn render_character_sheet(
+    player_query: Query<(&CombatStats, &Equipment), With<Player>>,
+    item_name_query: Query<&ItemName>,
+    mut character_sheet_query: Query<&mut Text, With<CharacterSheetText>>,
+) {
+    let (player, equipment) = player_query
+        .get_single()
+        .expect("Got more or less than exactly one Player entity while rendering UI");
+
+    let mut character_sheet = character_sheet_query
+        .get_single_mut()
+        .expect("Found more or less than exactly one character sheet while rendering UI");
+
+    let mut lines = vec![format!(
+        "Power: {}  Defense: {}",
+        player.power, player.defense
+    )];
+    for slot in EquipmentSlot::ALL {
+        let worn = equipment
+            .get(slot)
+            .map(|item| {
+                item_name_query
+                    .get(item)
+                    .map(|name| name.name.as_str())
+                    .unwrap_or(UNKNOWN_ITEM_NAME)
+            })
+            .unwrap_or("-");
+        lines.push(format!("{}: {}", slot.label(), worn));
+    }
+
+    // We only care for the first section
+    character_sheet.sections[0].value = lines.join("\n");
+}
+
 // TODO: Instead of creating new text sections on every rendering
 // we could just manipulate the existing text sections.
 // This might save some computing power.
@@ -239,6 +681,117 @@ fn render_action_log(
     action_log_text.sections = sections;
 }
 
+/// Moves the targeting cursor. It starts on the nearest monster in range, or the nearest tile in
+/// range other than the player's own if there is none. Tab cycles through the monsters in range,
+/// the movement keys move it freely within range and the mouse moves it to the hovered tile.
+#[allow(clippy::too_many_arguments)]
+fn move_targeting_cursor(
+    mut target_mode_query: Query<&mut TargetingModeContext>,
+    player_query: Query<(&Position, &Viewshed), With<Player>>,
+    monster_query: Query<&Position, With<Monster>>,
+    keyboard_input: Res<Input<KeyCode>>,
+    key_bindings: Res<KeyBindings>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    game_config: Res<GameConfig>,
+) {
+    let mut target_ctx = target_mode_query
+        .get_single_mut()
+        .expect("Expected a single TargetModeContext component in move_targeting_cursor");
+    let (player_pos, viewshed) = player_query
+        .get_single()
+        .expect("Expected a single player viewshed in move_targeting_cursor");
+
+    let range = target_ctx.range;
+    let mut monsters_in_range: Vec<&Position> = monster_query
+        .iter()
+        .filter(|pos| is_in_targeting_range(player_pos, viewshed, range, pos))
+        .collect();
+    monsters_in_range.sort_by_key(|pos| (player_pos.get_airline_distance(pos), pos.x, pos.y));
+
+    if target_ctx.cursor.is_none() {
+        target_ctx.cursor = initial_cursor(player_pos, viewshed, range, &monsters_in_range);
+    }
+    let cursor = match target_ctx.cursor.as_mut() {
+        Some(cursor) => cursor,
+        // Nothing but the player's own tile is in range
+        None => return,
+    };
+
+    if key_bindings.just_pressed(&keyboard_input, InputAction::NextTarget) {
+        if let Some(next) = next_target(&monsters_in_range, cursor) {
+            *cursor = next.clone();
+        }
+    }
+
+    let movement = get_movement_input(&keyboard_input, &key_bindings);
+    if movement.received_movement_input() {
+        let moved = Position::new(cursor.x + movement.x, cursor.y + movement.y);
+        if is_in_targeting_range(player_pos, viewshed, range, &moved) {
+            *cursor = moved;
+        }
+    }
+
+    if cursor_moved.iter().last().is_some() {
+        let window = windows.get_primary().expect("No primary window found");
+        let (camera, camera_transform) = camera_query.single();
+        if let Some(world_pos) = cursor_world_pos(window, camera, camera_transform) {
+            let hovered = screen_pos_to_map_pos(
+                world_pos,
+                game_config.tile_properties.tile_size,
+                &game_config.screen_dimensions,
+            );
+            if is_in_targeting_range(player_pos, viewshed, range, &hovered) {
+                *cursor = hovered;
+            }
+        }
+    }
+}
+
+/// Where the targeting cursor starts: on the nearest monster in range or, without one, on the
+/// nearest tile in range that isn't the player's own
+fn initial_cursor(
+    player_pos: &Position,
+    player_viewshed: &Viewshed,
+    range: u32,
+    monsters_in_range: &[&Position],
+) -> Option<Position> {
+    if let Some(nearest) = monsters_in_range.first() {
+        return Some((*nearest).clone());
+    }
+
+    player_viewshed
+        .visible_tiles
+        .iter()
+        .filter(|pos| {
+            *pos != player_pos && is_in_targeting_range(player_pos, player_viewshed, range, pos)
+        })
+        .min_by_key(|pos| (player_pos.get_airline_distance(pos), pos.x, pos.y))
+        .cloned()
+}
+
+/// Gets the target following the current one, wrapping around to the first
+fn next_target<'a>(targets: &[&'a Position], current: &Position) -> Option<&'a Position> {
+    let next_index = match targets.iter().position(|target| *target == current) {
+        Some(index) => (index + 1) % targets.len(),
+        None => 0,
+    };
+
+    targets.get(next_index).copied()
+}
+
+fn is_in_targeting_range(
+    player_pos: &Position,
+    player_viewshed: &Viewshed,
+    range: u32,
+    pos: &Position,
+) -> bool {
+    player_pos.get_airline_distance(pos) <= range as i32
+        && player_viewshed.visible_tiles.contains(pos)
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn render_target_mode(
     mut commands: Commands,
     app_state: ResMut<State<GameState>>,
@@ -246,7 +799,7 @@ fn render_target_mode(
     target_mode_query: Query<(Entity, &TargetingModeContext)>,
     tiles_query: Query<(&Position, With<Tile>)>,
     mut targeting_tiles_query: ParamSet<(
-        Query<(&GlobalTransform, &mut Sprite, &Position), With<TargetingTile>>,
+        Query<(&mut Sprite, &Position), With<TargetingTile>>,
         Query<Entity, With<TargetingTile>>,
     )>,
     windows: Res<Windows>,
@@ -254,14 +807,17 @@ fn render_target_mode(
     game_config: Res<GameConfig>,
     mouse_input: Res<Input<MouseButton>>,
     keyboard_input: Res<Input<KeyCode>>,
-    aoe_items: Query<&AreaOfEffect>,
+    aoe_items: Query<(&AreaOfEffect, Option<&AreaShape>)>,
     game_map: Res<GameMap>,
+    key_bindings: Res<KeyBindings>,
+    mut action_log: ResMut<ActionLog>,
+    mut redraw: EventWriter<RequestRedraw>,
 ) {
     let (target_ctx_entity, target_ctx) = target_mode_query
         .get_single()
         .expect("Expected a single TargetModeContext component in render_target_mode");
 
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    if key_bindings.just_pressed(&keyboard_input, InputAction::Cancel) {
         finish_targeting_mode(
             &mut commands,
             app_state,
@@ -269,8 +825,6 @@ fn render_target_mode(
             target_ctx_entity,
             targeting_tiles_query.p1(),
             None,
-            None,
-            &game_map,
         );
         return;
     }
@@ -290,66 +844,86 @@ fn render_target_mode(
             &game_config.screen_dimensions,
             &game_config.tile_properties,
         );
+        // Draw the cursor on the new tiles right away
+        redraw.send(RequestRedraw);
     } else {
         // We are waiting for the player to pick a target
-        let window = windows.get_primary().unwrap();
-
-        let aoe = aoe_items.get(target_ctx.item).ok();
+        draw_cursor_pos(target_ctx.cursor.as_ref(), targeting_tiles_query.p0());
 
-        if let Some(mouse_pos) = window.cursor_position() {
-            let target_pos =
-                draw_cursor_pos(window, mouse_pos, q_camera, targeting_tiles_query.p0());
+        let mut target_pos = None;
+        if key_bindings.just_pressed(&keyboard_input, InputAction::Confirm) {
+            target_pos = target_ctx.cursor.clone();
+        } else if mouse_input.just_pressed(MouseButton::Left) {
+            let window = windows.get_primary().unwrap();
+            let (camera, camera_transform) = q_camera.single();
+            target_pos = cursor_world_pos(window, camera, camera_transform)
+                .map(|world_pos| {
+                    screen_pos_to_map_pos(
+                        world_pos,
+                        game_config.tile_properties.tile_size,
+                        &game_config.screen_dimensions,
+                    )
+                })
+                .filter(|clicked| {
+                    targeting_tiles_query
+                        .p0()
+                        .iter()
+                        .any(|(_, tile_pos)| tile_pos == clicked)
+                });
+        }
 
-            if mouse_input.just_pressed(MouseButton::Left) && target_pos.is_some() {
-                finish_targeting_mode(
+        if let Some(target_pos) = target_pos {
+            let (player_pos, _, _) = viewshed_player_query
+                .get_single()
+                .expect("Expected a single player in render_target_mode");
+            let aoe = aoe_items
+                .get(target_ctx.item)
+                .ok()
+                .map(|(aoe, shape)| (aoe, shape.copied().unwrap_or_default()));
+            match confirmed_targets(player_pos, &target_pos, aoe, &game_map) {
+                Some(targets) => finish_targeting_mode(
                     &mut commands,
                     app_state,
                     target_ctx,
                     target_ctx_entity,
                     targeting_tiles_query.p1(),
-                    target_pos,
-                    aoe,
-                    &game_map,
-                );
-                return;
+                    Some(targets),
+                ),
+                None => action_log
+                    .entries
+                    .push("You can't target yourself.".to_owned()),
             }
         }
     }
 }
 
+/// The tiles hit by an item aimed at `target_pos`. Single target items can't be aimed at the
+/// player, which gives `None`.
+fn confirmed_targets(
+    player_pos: &Position,
+    target_pos: &Position,
+    aoe: Option<(&AreaOfEffect, AreaShape)>,
+    game_map: &GameMap,
+) -> Option<Vec<Position>> {
+    match aoe {
+        Some((aoe, shape)) => Some(affected_tiles(player_pos, target_pos, aoe, shape, game_map)),
+        None if target_pos == player_pos => None,
+        None => Some(vec![target_pos.clone()]),
+    }
+}
+
 fn finish_targeting_mode(
     commands: &mut Commands,
     mut app_state: ResMut<State<GameState>>,
     target_ctx: &TargetingModeContext,
     target_ctx_entity: Entity,
     targeting_tiles_entity_query: Query<Entity, With<TargetingTile>>,
-    target_pos: Option<Position>,
-    aoe: Option<&AreaOfEffect>,
-    game_map: &GameMap,
+    targets: Option<Vec<Position>>,
 ) {
     let mut perfomed_action = false;
 
     // If we have no target, just exit target mode
-    if let Some(pos) = target_pos {
-        let mut targets: Vec<Position> = vec![];
-        if aoe.is_some() {
-            // Fetch all targets from area
-            let viewshed = generate_viewshed(&pos, game_map, target_ctx.range as usize, true);
-            for x in 0..viewshed.width {
-                for y in 0..viewshed.height {
-                    if viewshed.is_in_fov(x, y) {
-                        let pos = Position {
-                            x: x as i32,
-                            y: y as i32,
-                        };
-                        targets.push(pos);
-                    }
-                }
-            }
-        } else {
-            targets.push(pos)
-        }
-
+    if let Some(targets) = targets {
         commands.spawn_empty().insert(WantsToUseItem {
             entity: target_ctx.item,
             targets: Some(targets),
@@ -383,86 +957,165 @@ fn create_targeting_tiles(
     screen_dimensions: &ScreenDimensions,
     tile_properties: &TileProperties,
 ) {
-    let visible_positions = &player_viewshed.visible_tiles;
-
-    let mut pos_in_range: HashSet<&Position> = HashSet::new();
-    for visible_pos in visible_positions {
-        if player_pos.get_airline_distance(visible_pos) <= target_ctx.range as i32 {
-            pos_in_range.insert(visible_pos);
-        }
-    }
-
-    let scaled_tile_size = tile_properties.get_scaled_tile_size();
     for (pos, _) in tiles_query.iter() {
-        if pos_in_range.contains(pos) {
+        if is_in_targeting_range(player_pos, player_viewshed, target_ctx.range, pos) {
             commands
                 .spawn_empty()
-                .insert(SpriteBundle {
-                    sprite: Sprite {
-                        color: TARGETING_MODE_TILE_COLOR,
-                        custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
-                        ..Default::default()
-                    },
-                    transform: Transform {
-                        translation: map_pos_to_screen_pos(
-                            pos,
-                            10.0,
-                            tile_properties.tile_size,
-                            screen_dimensions,
-                        ),
-                        scale: Vec3::new(
-                            tile_properties.tile_scale,
-                            tile_properties.tile_scale,
-                            1.0,
-                        ),
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                })
+                .insert(overlay_sprite(
+                    pos,
+                    TARGETING_MODE_TILE_COLOR,
+                    TARGETING_MODE_Z,
+                    tile_properties,
+                    screen_dimensions,
+                ))
                 .insert(Position { x: pos.x, y: pos.y })
                 .insert(TargetingTile {});
         }
     }
 }
 
+fn overlay_sprite(
+    pos: &Position,
+    color: Color,
+    z: f32,
+    tile_properties: &TileProperties,
+    screen_dimensions: &ScreenDimensions,
+) -> SpriteBundle {
+    let scaled_tile_size = tile_properties.get_scaled_tile_size();
+
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(scaled_tile_size, scaled_tile_size)),
+            ..Default::default()
+        },
+        transform: Transform {
+            translation: map_pos_to_screen_pos(
+                pos,
+                z,
+                tile_properties.tile_size,
+                screen_dimensions,
+            ),
+            scale: Vec3::new(tile_properties.tile_scale, tile_properties.tile_scale, 1.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Shows the area an area of effect item would hit at the targeting cursor, marking everyone
+/// visible that is caught in it, the player included.
+#[allow(clippy::too_many_arguments)]
+fn render_blast_preview(
+    mut commands: Commands,
+    target_mode_query: Query<&TargetingModeContext>,
+    aoe_items: Query<(&AreaOfEffect, Option<&AreaShape>)>,
+    preview_query: Query<Entity, With<BlastPreviewTile>>,
+    player_query: Query<(&Position, &Viewshed), With<Player>>,
+    combatant_query: Query<&Position, With<CombatStats>>,
+    game_map: Res<GameMap>,
+    game_config: Res<GameConfig>,
+) {
+    for preview_tile in preview_query.iter() {
+        commands.entity(preview_tile).despawn();
+    }
+
+    let target_ctx = target_mode_query
+        .get_single()
+        .expect("Expected a single TargetModeContext component in render_blast_preview");
+    let (cursor, (aoe, shape)) = match (&target_ctx.cursor, aoe_items.get(target_ctx.item)) {
+        (Some(cursor), Ok(aoe)) => (cursor, aoe),
+        _ => return,
+    };
+    let (player_pos, viewshed) = player_query
+        .get_single()
+        .expect("Expected a single player viewshed in render_blast_preview");
+
+    let shape = shape.copied().unwrap_or_default();
+    for pos in affected_tiles(player_pos, cursor, aoe, shape, &game_map) {
+        if !viewshed.visible_tiles.contains(&pos) {
+            continue;
+        }
+
+        let color = if combatant_query.iter().any(|victim_pos| *victim_pos == pos) {
+            BLAST_PREVIEW_VICTIM_COLOR
+        } else {
+            BLAST_PREVIEW_COLOR
+        };
+        commands
+            .spawn_empty()
+            .insert(overlay_sprite(
+                &pos,
+                color,
+                BLAST_PREVIEW_Z,
+                &game_config.tile_properties,
+                &game_config.screen_dimensions,
+            ))
+            .insert(BlastPreviewTile {});
+    }
+}
+
+fn clear_blast_preview(
+    mut commands: Commands,
+    preview_query: Query<Entity, With<BlastPreviewTile>>,
+) {
+    for preview_tile in preview_query.iter() {
+        commands.entity(preview_tile).despawn();
+    }
+}
+
+/// Highlights the targeting tile under the targeting cursor
 fn draw_cursor_pos(
-    window: &Window,
-    mouse_pos: Vec2,
-    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
-    mut targeting_tiles_query: Query<
-        (&GlobalTransform, &mut Sprite, &Position),
-        With<TargetingTile>,
-    >,
-) -> Option<Position> {
-    let (camera, camera_transform) = camera_query.single();
-    // get the size of the window
-    let window_size = Vec2::new(window.width() as f32, window.height() as f32);
-
-    // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
-    let ndc = (mouse_pos / window_size) * 2.0 - Vec2::ONE;
-
-    // matrix for undoing the projection and camera transform
-    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
-
-    // use it to convert ndc to world-space coordinates
-    let world_pos = ndc_to_world.project_point3(ndc.extend(-1.0));
-
-    // reduce it to a 2D value
-    let world_pos: Vec2 = world_pos.truncate();
-    // cursor is in the screen
-    let mut target_position: Option<Position> = None;
-    for (transform, mut sprite, position) in targeting_tiles_query.iter_mut() {
-        if transform.translation().x <= world_pos.x
-            && transform.translation().x + sprite.custom_size.unwrap().x >= world_pos.x
-            && transform.translation().y <= world_pos.y
-            && transform.translation().y + sprite.custom_size.unwrap().y >= world_pos.y
-        {
-            sprite.color = TARGETING_MODE_SELECTION_COLOR;
-            target_position = Some(position.clone());
+    cursor: Option<&Position>,
+    mut targeting_tiles_query: Query<(&mut Sprite, &Position), With<TargetingTile>>,
+) {
+    for (mut sprite, position) in targeting_tiles_query.iter_mut() {
+        sprite.color = if Some(position) == cursor {
+            TARGETING_MODE_SELECTION_COLOR
         } else {
-            sprite.color = TARGETING_MODE_TILE_COLOR
-        }
+            TARGETING_MODE_TILE_COLOR
+        };
     }
+}
+
+#[test]
+fn can_cycle_targets() {
+    let near = Position::new(1, 0);
+    let far = Position::new(3, 0);
+    let targets = vec![&near, &far];
+
+    assert_eq!(next_target(&targets, &near), Some(&far));
+    assert_eq!(next_target(&targets, &far), Some(&near));
+    assert_eq!(next_target(&targets, &Position::new(5, 5)), Some(&near));
+    assert_eq!(next_target(&[], &near), None);
+}
 
-    return target_position;
+#[test]
+fn confirm_without_monsters_in_range_does_not_target_the_player() {
+    let player_pos = Position::new(2, 2);
+    let viewshed = Viewshed {
+        visible_tiles: vec![
+            Position::new(2, 2),
+            Position::new(3, 2),
+            Position::new(5, 2),
+        ],
+        range: 8,
+        dirty: false,
+    };
+    let map = GameMap::default();
+
+    let cursor = initial_cursor(&player_pos, &viewshed, 6, &[]).unwrap();
+    assert_eq!(cursor, Position::new(3, 2));
+    assert_eq!(
+        confirmed_targets(&player_pos, &cursor, None, &map),
+        Some(vec![Position::new(3, 2)])
+    );
+
+    // Moving the cursor back onto the player doesn't make them a target
+    assert_eq!(
+        confirmed_targets(&player_pos, &player_pos, None, &map),
+        None
+    );
+    // Nothing but the player's tile in range
+    assert_eq!(initial_cursor(&player_pos, &viewshed, 0, &[]), None);
 }
@@ -1,5 +1,7 @@
 use bevy::prelude::{Input, KeyCode};
 
+use crate::configs::key_bindings::{InputAction, KeyBindings};
+
 pub struct MovementInput {
     pub x: i32,
     pub y: i32,
@@ -7,25 +9,44 @@ pub struct MovementInput {
 
 impl MovementInput {
     pub fn received_movement_input(&self) -> bool {
-        return self.x != 0 || self.y != 0;
+        self.x != 0 || self.y != 0
     }
 }
 
-pub fn get_movement_input(keyboard_input: &Input<KeyCode>) -> MovementInput {
+/// Sums up the directions of all movement keys pressed this frame
+pub fn get_movement_input(
+    keyboard_input: &Input<KeyCode>,
+    key_bindings: &KeyBindings,
+) -> MovementInput {
     let mut x: i32 = 0;
     let mut y: i32 = 0;
-    if keyboard_input.just_pressed(KeyCode::A) {
-        x = -1;
-    }
-    if keyboard_input.just_pressed(KeyCode::D) {
-        x = 1;
+    for action in InputAction::MOVEMENT {
+        if key_bindings.just_pressed(keyboard_input, action) {
+            let (dx, dy) = action.direction();
+            x += dx;
+            y += dy;
+        }
     }
-    if keyboard_input.just_pressed(KeyCode::W) {
-        y = 1;
-    }
-    if keyboard_input.just_pressed(KeyCode::S) {
-        y = -1;
+
+    MovementInput {
+        x: x.clamp(-1, 1),
+        y: y.clamp(-1, 1),
     }
+}
+
+#[test]
+fn can_combine_movement_keys() {
+    let key_bindings = KeyBindings::default();
+
+    let mut keyboard_input = Input::<KeyCode>::default();
+    keyboard_input.press(KeyCode::W);
+    keyboard_input.press(KeyCode::D);
+    let input = get_movement_input(&keyboard_input, &key_bindings);
+    assert_eq!((input.x, input.y), (1, 1));
 
-    MovementInput { x, y }
+    let mut keyboard_input = Input::<KeyCode>::default();
+    keyboard_input.press(KeyCode::Z);
+    keyboard_input.press(KeyCode::Numpad1);
+    let input = get_movement_input(&keyboard_input, &key_bindings);
+    assert_eq!((input.x, input.y), (-1, -1));
 }
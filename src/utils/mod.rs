@@ -1,3 +1,5 @@
+pub mod input_utils;
+pub mod random_table;
 pub mod rectangle;
 pub mod render;
-pub mod input_utils;
\ No newline at end of file
+pub mod rng;
@@ -0,0 +1,65 @@
+use rand::{rngs::StdRng, Rng};
+
+/// A table of entries that are rolled for by weight. An entry with twice the weight of another
+/// one comes up twice as often.
+pub struct RandomTable<T> {
+    entries: Vec<(T, u32)>,
+    total_weight: u32,
+}
+
+impl<T> RandomTable<T> {
+    pub fn new() -> RandomTable<T> {
+        RandomTable {
+            entries: vec![],
+            total_weight: 0,
+        }
+    }
+
+    /// Adds an entry to the table. Entries with a weight of zero or less are never rolled and
+    /// are left out.
+    pub fn add(mut self, entry: T, weight: i32) -> RandomTable<T> {
+        if weight > 0 {
+            self.total_weight += weight as u32;
+            self.entries.push((entry, weight as u32));
+        }
+        self
+    }
+
+    /// Picks a random entry according to its weight, or `None` if the table is empty
+    pub fn roll(&self, rng: &mut StdRng) -> Option<&T> {
+        if self.total_weight == 0 {
+            return None;
+        }
+
+        let mut roll = rng.gen_range(0..self.total_weight);
+        for (entry, weight) in &self.entries {
+            if roll < *weight {
+                return Some(entry);
+            }
+            roll -= weight;
+        }
+
+        None
+    }
+}
+
+#[test]
+fn can_roll_by_weight() {
+    use rand::SeedableRng;
+
+    let mut rng = StdRng::seed_from_u64(1);
+
+    let empty: RandomTable<&str> = RandomTable::new().add("never", 0);
+    assert_eq!(empty.roll(&mut rng), None);
+
+    let single = RandomTable::new().add("always", 3).add("never", -1);
+    for _ in 0..100 {
+        assert_eq!(single.roll(&mut rng), Some(&"always"));
+    }
+
+    let weighted = RandomTable::new().add("common", 9).add("rare", 1);
+    let rare_count = (0..1000)
+        .filter(|_| weighted.roll(&mut rng) == Some(&"rare"))
+        .count();
+    assert!(rare_count > 50 && rare_count < 150);
+}
@@ -27,10 +27,10 @@ impl Rectangle {
     }
 
     pub fn width(&self) -> i32 {
-        return self.x2 - self.x1;
+        self.x2 - self.x1
     }
 
     pub fn height(&self) -> i32 {
-        return self.y2 - self.y1;
+        self.y2 - self.y1
     }
 }
@@ -1,4 +1,8 @@
-use bevy::math::Vec3;
+use bevy::{
+    math::{Vec2, Vec3},
+    prelude::{Camera, GlobalTransform},
+    window::Window,
+};
 
 use crate::{components::position::Position, ScreenDimensions};
 
@@ -17,3 +21,53 @@ pub fn map_pos_to_screen_pos(
         z_coord,
     )
 }
+
+/// Inverse of `map_pos_to_screen_pos`: finds the map tile whose sprite covers a world position
+pub fn screen_pos_to_map_pos(
+    world_pos: Vec2,
+    tile_size: f32,
+    screen_dimensions: &ScreenDimensions,
+) -> Position {
+    let x = (world_pos.x + screen_dimensions.screen_width / 2.0) / tile_size;
+    let y = (world_pos.y + screen_dimensions.screen_height / 2.0
+        - screen_dimensions.screen_height * 0.2)
+        / tile_size;
+
+    // Sprites are centered on their translation
+    Position::new(x.round() as i32, y.round() as i32)
+}
+
+/// Gets the world position under the mouse cursor, if the cursor is inside the window
+pub fn cursor_world_pos(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let mouse_pos = window.cursor_position()?;
+    let window_size = Vec2::new(window.width(), window.height());
+
+    // convert screen position [0..resolution] to ndc [-1..1] (gpu coordinates)
+    let ndc = (mouse_pos / window_size) * 2.0 - Vec2::ONE;
+
+    // matrix for undoing the projection and camera transform
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+
+    // use it to convert ndc to world-space coordinates and reduce it to a 2D value
+    Some(ndc_to_world.project_point3(ndc.extend(-1.0)).truncate())
+}
+
+#[test]
+fn can_convert_screen_pos_back_to_map_pos() {
+    let screen_dimensions = ScreenDimensions {
+        screen_height: 720.0,
+        screen_width: 1280.0,
+    };
+    let map_pos = Position::new(12, 7);
+    let screen_pos = map_pos_to_screen_pos(&map_pos, 0.0, 16.0, &screen_dimensions);
+
+    let near_center = screen_pos.truncate() + Vec2::new(7.0, -7.0);
+    assert_eq!(
+        screen_pos_to_map_pos(near_center, 16.0, &screen_dimensions),
+        map_pos
+    );
+}
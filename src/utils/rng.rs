@@ -0,0 +1,52 @@
+use bevy::prelude::Resource;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The source of randomness for the game. Every dungeon level is generated from its own RNG
+/// derived from the seed and the depth, so the same seed always produces the same dungeon no
+/// matter what happened on earlier levels. Combat, loot and hunger draw from `rng`.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    pub rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> GameRng {
+        GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// RNG for generating the level at the given depth
+    pub fn level_rng(&self, depth: i32) -> StdRng {
+        StdRng::seed_from_u64(self.seed ^ depth as u64)
+    }
+
+    /// Re-seeds the gameplay RNG from itself and returns that new seed. A game restored from it
+    /// with `GameRng::restore` gets the same rolls as the running one.
+    pub fn save_state(&mut self) -> u64 {
+        let state = self.rng.gen();
+        self.rng = StdRng::seed_from_u64(state);
+        state
+    }
+
+    pub fn restore(seed: u64, state: u64) -> GameRng {
+        GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(state),
+        }
+    }
+}
+
+#[test]
+fn restored_rng_continues_like_the_saved_one() {
+    let mut running = GameRng::new(42);
+    running.rng.gen::<u32>();
+
+    let state = running.save_state();
+    let mut restored = GameRng::restore(running.seed, state);
+
+    assert_eq!(restored.seed, 42);
+    assert_eq!(restored.rng.gen::<u64>(), running.rng.gen::<u64>());
+}
@@ -1,5 +1,7 @@
+use std::collections::HashSet;
+
 use bevy::ecs::component::Component;
-use bevy::prelude::{Plugin, Query, Res, ResMut, With};
+use bevy::prelude::{Entity, IntoSystemDescriptor, Plugin, Query, Res, ResMut, With};
 use doryen_fov::{FovAlgorithm, FovRecursiveShadowCasting, MapData};
 
 use crate::{
@@ -9,6 +11,8 @@ use crate::{
     player::Player,
 };
 
+pub const PLAYER_VIEWSHED_LABEL: &str = "player_viewshed";
+
 pub struct ViewshedPlugin;
 
 #[derive(Component)]
@@ -20,7 +24,7 @@ pub struct Viewshed {
 
 impl Plugin for ViewshedPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system(populate_viewshed_player);
+        app.add_system(populate_viewshed_player.label(PLAYER_VIEWSHED_LABEL));
         app.add_system(populate_viewshed_monsters);
     }
 }
@@ -29,20 +33,22 @@ fn populate_viewshed_player(
     mut map: ResMut<GameMap>,
     mut viewshed_player: Query<(&Position, &mut Viewshed, With<Player>)>,
 ) {
-    let (entity_pos, mut viewshed, _) = viewshed_player.single_mut();
-    let temp_map = generate_viewshed(entity_pos, &map, viewshed.range as usize, true);
+    // There is no player while a new game is being generated
+    if let Ok((entity_pos, mut viewshed, _)) = viewshed_player.get_single_mut() {
+        let temp_map = generate_viewshed(entity_pos, &map, viewshed.range as usize, true);
 
-    // Now find all the tiles that are visible and translate to real game map
-    viewshed.visible_tiles.clear();
-    for x in 0..temp_map.width {
-        for y in 0..temp_map.height {
-            if temp_map.is_in_fov(x, y) {
-                let pos = Position {
-                    x: x as i32,
-                    y: y as i32,
-                };
-                viewshed.visible_tiles.push(pos.clone());
-                map.visited_tiles.insert(pos);
+        // Now find all the tiles that are visible and translate to real game map
+        viewshed.visible_tiles.clear();
+        for x in 0..temp_map.width {
+            for y in 0..temp_map.height {
+                if temp_map.is_in_fov(x, y) {
+                    let pos = Position {
+                        x: x as i32,
+                        y: y as i32,
+                    };
+                    viewshed.visible_tiles.push(pos.clone());
+                    map.visited_tiles.insert(pos);
+                }
             }
         }
     }
@@ -104,7 +110,19 @@ pub fn generate_viewshed(
         walls_are_blocking,
     );
 
-    return temp_map;
+    temp_map
+}
+
+/// Remembers the monsters in sight and tells whether any of them wasn't in sight before. Nothing
+/// counts as new while no monsters are known yet, e.g. on the first step of auto-exploring.
+pub fn spot_new_monsters(known: &mut Option<HashSet<Entity>>, in_sight: HashSet<Entity>) -> bool {
+    let spotted = match known {
+        Some(known) => in_sight.iter().any(|monster| !known.contains(monster)),
+        None => false,
+    };
+    *known = Some(in_sight);
+
+    spotted
 }
 
 // TODO: Revisit and figure out why this didnt work because I really think this could save computing power
@@ -152,3 +170,19 @@ fn _populate_viewshed_weird(map: Res<GameMap>, mut query: Query<(&Position, &mut
         }
     }
 }
+
+#[test]
+fn only_monsters_coming_into_sight_are_new() {
+    let rat = Entity::from_raw(1);
+    let orc = Entity::from_raw(2);
+    let mut known = None;
+
+    // Already in sight when starting out
+    assert!(!spot_new_monsters(&mut known, HashSet::from([rat])));
+    assert!(!spot_new_monsters(&mut known, HashSet::from([rat])));
+    assert!(spot_new_monsters(&mut known, HashSet::from([rat, orc])));
+
+    // Losing sight of a monster and seeing it again counts as new
+    assert!(!spot_new_monsters(&mut known, HashSet::new()));
+    assert!(spot_new_monsters(&mut known, HashSet::from([orc])));
+}